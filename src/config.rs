@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Directory (relative to the working directory) that user config files are stored in.
+#[cfg(not(target_arch = "wasm32"))]
+const CONFIG_DIR: &str = "config";
+
+/// Loads `file_name` from the config directory, returning `None` if it is missing or malformed.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn load<T: DeserializeOwned>(file_name: &str) -> Option<T> {
+    load_from(CONFIG_DIR, file_name)
+}
+
+/// Writes `value` to `file_name` in the config directory.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn save<T: Serialize>(file_name: &str, value: &T) {
+    save_to(CONFIG_DIR, file_name, value)
+}
+
+/// Like [`load`], but from `dir` instead of the config directory.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn load_from<T: DeserializeOwned>(dir: &str, file_name: &str) -> Option<T> {
+    let path = std::path::Path::new(dir).join(file_name);
+    let contents = std::fs::read_to_string(&path).ok()?;
+    match ron::from_str(&contents) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            warn!("Ignoring malformed config {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+/// Like [`save`], but into `dir` instead of the config directory.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn save_to<T: Serialize>(dir: &str, file_name: &str, value: &T) {
+    let path = std::path::Path::new(dir).join(file_name);
+    let contents = match ron::ser::to_string_pretty(
+        value,
+        ron::ser::PrettyConfig::default().compact_arrays(true),
+    ) {
+        Ok(contents) => contents,
+        Err(e) => {
+            error!("Failed to serialize {}: {e}", path.display());
+            return;
+        }
+    };
+    if let Err(e) = std::fs::create_dir_all(dir).and_then(|_| std::fs::write(&path, contents)) {
+        error!("Failed to write {}: {e}", path.display());
+    } else {
+        info!("Saved {}", path.display());
+    }
+}
+
+// There is no filesystem in the browser, so the web build always runs with defaults.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn load<T: DeserializeOwned>(_file_name: &str) -> Option<T> {
+    None
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn save<T: Serialize>(file_name: &str, _value: &T) {
+    warn!("Saving {file_name} is not supported on the web");
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn load_from<T: DeserializeOwned>(_dir: &str, _file_name: &str) -> Option<T> {
+    None
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn save_to<T: Serialize>(_dir: &str, file_name: &str, _value: &T) {
+    warn!("Saving {file_name} is not supported on the web");
+}
@@ -1,27 +1,31 @@
 //! Skinned mesh example with mesh and joints data loaded from a glTF file.
 //! Example taken from <https://github.com/KhronosGroup/glTF-Tutorials/blob/master/gltfTutorial/gltfTutorial_019_SimpleSkin.md>
 
-use std::f32::consts::*;
 // use std::ops::Mul;
 
 use bevy::asset::AssetMetaCheck;
 use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
-use bevy::render::mesh::shape::UVSphere;
 // use bevy::ecs::schedule::{LogLevel, ScheduleBuildSettings};
 use bevy::render::mesh::{Mesh, PrimitiveTopology};
 use bevy::render::view::NoFrustumCulling;
 use bevy::utils::HashMap;
-use bevy::{
-    pbr::AmbientLight,
-    prelude::*,
-    render::mesh::{skinning::SkinnedMesh, Indices},
-};
+use bevy::{pbr::AmbientLight, prelude::*, render::mesh::Indices};
 use bevy_xpbd_3d::prelude::*;
 use noise::{NoiseFn, Perlin};
 use plugins::camera::CameraTarget;
+use plugins::cloth::WingSurfaces;
+use plugins::control_mode::ControlMode;
+use plugins::controls::InputAction;
 use plugins::poop::Poop;
+use plugins::power_ups::{Collected, CollectibleKind, PowerUps};
+use plugins::replay::{RngStream, RunSeed};
 use plugins::score::{ScorePlugin, ScoreState, ScoreTarget};
+use plugins::species::SelectedSpecies;
+use plugins::time_trial::TimeTrialSeedSet;
+use plugins::tuning::FlightParams;
+use plugins::wind::WindField;
 
+mod config;
 mod plugins;
 
 #[derive(PhysicsLayer)]
@@ -57,6 +61,14 @@ impl BirbState {
 #[derive(Component)]
 struct Terrain;
 
+/// Freshly streamed in chunks fade from transparent to opaque instead of popping in.
+#[derive(Component)]
+struct ChunkFadeIn {
+    age: f32,
+}
+
+const CHUNK_FADE_SECS: f32 = 1.5;
+
 #[derive(Resource)]
 struct TerrainState {
     chunk_size: u32,
@@ -90,7 +102,11 @@ impl TerrainState {
 }
 
 #[derive(Component)]
-struct Collectible;
+struct Collectible(CollectibleKind);
+
+/// Sent when the birb hits the ground and is put back up in the air.
+#[derive(Event)]
+struct Respawned;
 
 #[derive(Resource)]
 struct GameState {
@@ -102,7 +118,9 @@ struct Birb;
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, States)]
 enum AppState {
+    /// Picking a species before the run.
     #[default]
+    SelectSpecies,
     InGame,
     Paused,
 }
@@ -134,26 +152,65 @@ fn main() {
             brightness: 1.0,
             ..default()
         })
+        .add_event::<Respawned>()
         .insert_resource(GameState {
             waypoints_achieved_counter: 0,
         })
         .add_state::<AppState>()
         .insert_resource(BirbState::new())
         .insert_resource(TerrainState::new(128, 512.0 * CHUNK_SIZE_WORLD_SPACE_MUL))
+        .add_plugins(plugins::controls::ControlsPlugin)
+        .add_plugins(plugins::replay::ReplayPlugin)
+        .add_plugins(plugins::species::SpeciesPlugin)
+        .add_plugins(plugins::customization::CustomizationPlugin)
+        .add_plugins(plugins::courses::CoursesPlugin)
+        .add_plugins(plugins::time_trial::TimeTrialPlugin)
+        .add_plugins(plugins::control_mode::ControlModePlugin)
+        .add_plugins(plugins::assist::AssistPlugin)
+        .add_plugins(plugins::gamepad::GamepadPlugin)
+        .add_plugins(plugins::scan_codes::ScanCodesPlugin)
+        .add_plugins(plugins::touch::TouchPlugin)
+        .add_plugins(plugins::wing_hud::WingHudPlugin)
+        .add_plugins(plugins::typing::TypingPlugin)
         .add_plugins(ScorePlugin)
         .add_plugins(plugins::poop::PoopPlugin)
-        .insert_resource(ScoreState { distance: 0.0, hi_score: 0.0 })
+        .add_plugins(plugins::power_ups::PowerUpsPlugin)
+        .add_plugins(plugins::tuning::TuningPlugin)
+        .add_plugins(plugins::wind::WindPlugin)
+        .add_plugins(plugins::wind_streaks::WindStreaksPlugin)
+        .add_plugins(plugins::trails::TrailsPlugin)
+        .add_plugins(plugins::weather::WeatherPlugin)
+        .add_plugins(plugins::day_night::DayNightPlugin)
+        .add_plugins(plugins::skeleton::SkeletonPlugin)
+        .add_plugins(plugins::secondary::SecondaryPlugin)
+        .add_plugins(plugins::cloth::ClothPlugin)
+        .add_plugins(plugins::animation::BirbAnimationPlugin)
+        .insert_resource(ScoreState {
+            distance: 0.0,
+            hi_score: 0.0,
+            bonus: 0.0,
+            multiplier: 1.0,
+            typing: default(),
+        })
         .add_systems(Startup, setup)
+        .add_systems(
+            OnExit(AppState::SelectSpecies),
+            (spawn_birb, spawn_collectibles.after(TimeTrialSeedSet)),
+        )
         .add_systems(
             Update,
             (
                 update_terrain_system,
+                fade_in_chunks,
                 move_terrain,
                 respawn_birb_when_grounded,
             )
                 .run_if(in_state(AppState::InGame)),
         )
-        .add_systems(Update, (birb_inputs, birb_physics_update, joint_animation))
+        .add_systems(
+            Update,
+            (birb_inputs, birb_physics_update, joint_animation).chain(),
+        )
         .add_systems(Update, menu_stuff)
         .add_systems(Update, debug_keys)
         .add_plugins(plugins::camera::ControllerPlugin)
@@ -166,13 +223,49 @@ fn main() {
         .run();
 }
 
+fn spawn_birb(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    flight_params: Res<FlightParams>,
+    species: Res<SelectedSpecies>,
+) {
+    let stats = species.0.stats();
+    commands
+        .spawn(SpatialBundle::from_transform(BIRB_SPAWN))
+        .insert((
+            RigidBody::Dynamic,
+            LinearDamping(flight_params.linear_damping * stats.linear_damping),
+            AngularDamping(flight_params.angular_damping * stats.angular_damping),
+            Collider::ball(stats.collider_radius),
+            ExternalForce::default().with_persistence(false),
+        ))
+        .insert(CameraTarget)
+        .insert(stats.skeleton)
+        .insert(ScoreTarget {
+            last_pos: BIRB_SPAWN.translation,
+        })
+        .insert(CollisionLayers::new(
+            [Layer::Player],
+            [Layer::Enemy, Layer::Ground, Layer::Poop, Layer::Collectible],
+        ))
+        .insert(Birb)
+        .with_children(|birb| {
+            // The model is scaled on its own so the collider keeps the species' size.
+            birb.spawn(SceneBundle {
+                scene: asset_server.load(stats.scene),
+                transform: Transform::from_scale(Vec3::splat(stats.scale)),
+                ..default()
+            });
+        });
+}
+
 fn menu_stuff(
     mut physics_time: ResMut<Time<Physics>>,
     current_state: Res<State<AppState>>,
     mut next_state: ResMut<NextState<AppState>>,
-    inputs: Res<Input<KeyCode>>,
+    actions: Res<Input<InputAction>>,
 ) {
-    if inputs.just_pressed(KeyCode::Escape) {
+    if actions.just_pressed(InputAction::Pause) {
         match **current_state {
             AppState::InGame => {
                 next_state.set(AppState::Paused);
@@ -182,6 +275,7 @@ fn menu_stuff(
                 next_state.set(AppState::InGame);
                 physics_time.unpause();
             }
+            AppState::SelectSpecies => {}
         }
     }
 }
@@ -190,9 +284,9 @@ fn debug_keys(
     mut commands: Commands,
     mut birb_visiblity: Query<&mut Visibility, With<Birb>>,
     mut birb_physics: Query<Entity, With<Birb>>,
-    inputs: Res<Input<KeyCode>>,
+    actions: Res<Input<InputAction>>,
 ) {
-    if inputs.just_pressed(KeyCode::F8) {
+    if actions.just_pressed(InputAction::ToggleBirbVisibility) {
         for mut b in &mut birb_visiblity {
             match *b {
                 Visibility::Visible | Visibility::Inherited => {
@@ -204,66 +298,49 @@ fn debug_keys(
             }
         }
     }
-    if inputs.just_pressed(KeyCode::F9) {
+    if actions.just_pressed(InputAction::DisableBirbPhysics) {
         for b in &mut birb_physics {
             commands.entity(b).remove::<RigidBody>();
         }
     }
 }
 
-fn setup(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-) {
+fn setup(mut commands: Commands, terrain_state: Res<TerrainState>) {
     // Create a camera
     commands.spawn(Camera3dBundle {
         transform: Transform::from_xyz(0.0, 4.5, 7.0).looking_at(Vec3::ZERO, Vec3::Y),
+        projection: PerspectiveProjection {
+            far: terrain_state.view_radius,
+            ..default()
+        }
+        .into(),
         ..default()
     });
 
-    // Spawn the first scene in `models/SimpleSkin/SimpleSkin.gltf`
-    commands
-        .spawn(SceneBundle {
-            scene: asset_server.load("models/birb2.gltf#Scene0"),
-            transform: BIRB_SPAWN,
-            ..default()
-        })
-        .insert((
-            RigidBody::Dynamic,
-            LinearDamping(0.16),
-            AngularDamping(1.6),
-            Collider::ball(0.5),
-            ExternalForce::default().with_persistence(false),
-        ))
-        .insert(CameraTarget)
-        .insert(ScoreTarget {
-            last_pos: BIRB_SPAWN.translation,
-        })
-        .insert(CollisionLayers::new(
-            [Layer::Player],
-            [Layer::Enemy, Layer::Ground, Layer::Poop, Layer::Collectible],
-        ))
-        .insert(Birb);
-
-    commands.spawn(DirectionalLightBundle {
-        ..Default::default()
-    });
+    // generate_terrain(&mut commands, &mut meshes, &mut materials);
+}
 
+/// Spawned once the run starts, so a time trial can pick the seed first.
+fn spawn_collectibles(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    seed: Res<RunSeed>,
+) {
     let scale = 1000.0; // Scale for noise coordinates
     let radius = 10.0;
+    let mut rng = seed.rng(RngStream::Collectibles);
+    let mut kind_rng = seed.rng(RngStream::CollectibleKinds);
+    let kind_assets: HashMap<_, _> = CollectibleKind::ALL
+        .into_iter()
+        .map(|kind| {
+            let mesh = meshes.add(kind.mesh(radius));
+            let material = materials.add(kind.color().into());
+            (kind, (mesh, material))
+        })
+        .collect();
     // spawn collectibles
     for _ in 0..100 {
-        // Create an icosphere
-        let uvsphere_mesh = Mesh::from(UVSphere {
-            radius,
-            sectors: 14,
-            stacks: 14,
-        });
-
-        let mut rng = rand::thread_rng();
-
         use rand::Rng;
 
         // Use Perlin noise for position
@@ -277,19 +354,19 @@ fn setup(
         //     i as f32 * 10.0, 10.0, 10.0
         // );
 
+        let kind = CollectibleKind::random(&mut kind_rng);
+        let (mesh, material) = &kind_assets[&kind];
         commands
             .spawn(PbrBundle {
-                mesh: meshes.add(uvsphere_mesh),
-                material: materials.add(Color::rgb(1.0, 0.843, 0.0).into()),
+                mesh: mesh.clone(),
+                material: material.clone(),
                 transform: Transform::from_translation(position),
                 ..Default::default()
             })
             .insert((Sensor, Collider::ball(radius * 1.2)))
             .insert(CollisionLayers::new([Layer::Collectible], [Layer::Player]))
-            .insert(Collectible);
+            .insert(Collectible(kind));
     }
-
-    // generate_terrain(&mut commands, &mut meshes, &mut materials);
 }
 
 const BIRB_SPAWN: Transform = Transform::from_xyz(0.0, 100.0, 0.0);
@@ -362,6 +439,22 @@ fn update_terrain_system(
 }
 
 const CHUNK_SIZE_WORLD_SPACE_MUL: f32 = 12.0;
+/// Maximum elevation of the terrain.
+const TERRAIN_MAX_HEIGHT: f32 = 15.0;
+/// How far the terrain chunks sit below the generated heights.
+const TERRAIN_OFFSET: f32 = -10.0;
+const TERRAIN_SEED: u32 = 1337;
+const TERRAIN_NOISE_SCALE: f64 = 0.01;
+
+/// World-space height of the ground below `x`, `z`.
+fn terrain_height(x: f32, z: f32) -> f32 {
+    let perlin = Perlin::new(TERRAIN_SEED);
+    let p = [
+        x as f64 * TERRAIN_NOISE_SCALE,
+        z as f64 * TERRAIN_NOISE_SCALE,
+    ];
+    perlin.get(p) as f32 * TERRAIN_MAX_HEIGHT + TERRAIN_OFFSET
+}
 
 // Assuming generate_terrain_chunk is defined to generate a single chunk at specified coordinates.
 fn generate_terrain_chunk(
@@ -372,8 +465,8 @@ fn generate_terrain_chunk(
     chunk_z: f32,
     chunk_size: u32, // Assuming chunk_size is the number of vertices along one edge of the chunk
 ) -> Entity {
-    let max_height = 15.0; // Maximum elevation of the terrain
-    let perlin = Perlin::new(1337); // Perlin noise generator
+    let max_height = TERRAIN_MAX_HEIGHT;
+    let perlin = Perlin::new(TERRAIN_SEED); // Perlin noise generator
 
     let mut positions = Vec::new();
     let mut normals = Vec::new();
@@ -385,7 +478,7 @@ fn generate_terrain_chunk(
         for z in 0..=chunk_size {
             let world_x = chunk_x + x as f32 * CHUNK_SIZE_WORLD_SPACE_MUL;
             let world_z = chunk_z + z as f32 * CHUNK_SIZE_WORLD_SPACE_MUL;
-            let perlin_scale = 0.01;
+            let perlin_scale = TERRAIN_NOISE_SCALE;
             let p = [world_x as f64 * perlin_scale, world_z as f64 * perlin_scale];
             let height = perlin.get(p) as f32 * max_height;
 
@@ -453,9 +546,13 @@ fn generate_terrain_chunk(
     commands
         .spawn(PbrBundle {
             // transform: Transform::from_xyz(chunk_x, 0.0, chunk_z),
-            transform: Transform::from_xyz(0.0, -10.0, 0.0),
+            transform: Transform::from_xyz(0.0, TERRAIN_OFFSET, 0.0),
             mesh: meshes.add(mesh.clone()),
-            material: materials.add(Color::GREEN.into()),
+            material: materials.add(StandardMaterial {
+                base_color: Color::GREEN.with_a(0.0),
+                alpha_mode: AlphaMode::Blend,
+                ..default()
+            }),
             ..default()
         })
         .insert((
@@ -468,9 +565,31 @@ fn generate_terrain_chunk(
         ))
         .insert(NoFrustumCulling)
         .insert(Terrain)
+        .insert(ChunkFadeIn { age: 0.0 })
         .id()
 }
 
+fn fade_in_chunks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut chunks: Query<(Entity, &Handle<StandardMaterial>, &mut ChunkFadeIn)>,
+) {
+    for (entity, material, mut fade) in &mut chunks {
+        fade.age += time.delta_seconds();
+        let Some(material) = materials.get_mut(material) else {
+            continue;
+        };
+        let alpha = (fade.age / CHUNK_FADE_SECS).min(1.0);
+        material.base_color.set_a(alpha);
+        if alpha >= 1.0 {
+            // Opaque chunks are cheaper to draw and sort correctly again.
+            material.alpha_mode = AlphaMode::Opaque;
+            commands.entity(entity).remove::<ChunkFadeIn>();
+        }
+    }
+}
+
 fn move_terrain(
     keyboard_input: Res<Input<KeyCode>>,
     mut query: Query<&mut Transform, With<Terrain>>,
@@ -501,143 +620,99 @@ fn move_terrain(
     }
 }
 
-/// The scene hierarchy currently looks somewhat like this:
-///
-/// ```ignore
-/// <Parent entity>
-///   + Mesh node (without `PbrBundle` or `SkinnedMesh` component)
-///     + Skinned mesh entity (with `PbrBundle` and `SkinnedMesh` component, created by glTF loader)
-///     + First joint
-///       + Second joint
-/// ```
-///
-/// In this example, we want to get and animate the second joint.
-/// It is similar to the animation defined in `models/SimpleSkin/SimpleSkin.gltf`.
+/// Rotates the wing joints bound by the [`SkeletonPlugin`](plugins::skeleton::SkeletonPlugin)
+/// to their current angles.
 fn joint_animation(
-    parent_query: Query<&Parent, With<SkinnedMesh>>,
-    children_query: Query<&Children>,
     mut transform_query: Query<&mut Transform>,
     mut birb_state: ResMut<BirbState>,
-    time: Res<Time>,
-    // names: Query<&Name>,
+    wind: Res<WindField>,
 ) {
-    // Iter skinned mesh entity
-    for skinned_mesh_parent in &parent_query {
-        // dbg!(&skinned_mesh_parent);
-        // Mesh node is the parent of the skinned mesh entity.
-        let mesh_node_entity = skinned_mesh_parent.get();
-        // println!(
-        //     "Parent: {}",
-        //     names
-        //         .get(mesh_node_entity)
-        //         .map(|x| x.as_str())
-        //         .unwrap_or("No Name")
-        // );
-        // for desc in children_query.iter_descendants(mesh_node_entity) {
-        // dbg!(&desc);
-        // println!(
-        //     "{desc:?} {}",
-        //     names.get(desc).map(|x| x.as_str()).unwrap_or("No Name")
-        // );
-        //     dbg!(&world.inspect_entity(desc));
-        // }
-
-        // Get `Children` in the mesh node.
-        if birb_state.wing_joints.is_none() {
-            let mesh_node_children = children_query.get(mesh_node_entity).unwrap();
-
-            let center_bone = mesh_node_children[1];
-            let center_bone_children = children_query.get(center_bone).unwrap();
-
-            let left1 = center_bone_children[1];
-            let right1 = center_bone_children[0];
-
-            let left2 = children_query.get(left1).unwrap()[0];
-            let right2 = children_query.get(right1).unwrap()[0];
-
-            let left3 = children_query.get(left2).unwrap()[0];
-            let right3 = children_query.get(right2).unwrap()[0];
-
-            let left4 = children_query.get(left3).unwrap()[0];
-            let right4 = children_query.get(right3).unwrap()[0];
-
-            birb_state.wing_joints = Some(vec![
-                left4, left3, left2, left1, right1, right2, right3, right4,
-            ]);
-        }
-        if birb_state.original_rots.is_none() {
-            let mut prev_rots = vec![];
-            for entity in birb_state.wing_joints.as_ref().unwrap() {
-                prev_rots.push(transform_query.get_mut(*entity).unwrap().rotation);
-            }
-            birb_state.original_rots = Some(prev_rots);
-        }
-        for ((entity, angle), orig_rot) in birb_state
-            .wing_joints
-            .as_ref()
-            .unwrap()
+    let birb_state = &mut *birb_state;
+    let Some(wing_joints) = birb_state.wing_joints.as_ref() else {
+        return;
+    };
+    let original_rots = birb_state.original_rots.get_or_insert_with(|| {
+        wing_joints
             .iter()
-            .zip(birb_state.angles.iter())
-            .zip(birb_state.original_rots.as_ref().unwrap().iter())
-        {
-            let wing_joint_transform = &mut transform_query.get_mut(*entity).unwrap();
-            let wind_force: Quat =
-                calculate_turbulence_rotation(&time, wing_joint_transform.translation);
-            let rot = &mut wing_joint_transform.rotation;
-            *rot = wind_force * *orig_rot * Quat::from_rotation_x(*angle);
-        }
+            .map(|entity| {
+                transform_query
+                    .get(*entity)
+                    .map_or(Quat::IDENTITY, |t| t.rotation)
+            })
+            .collect()
+    });
+    for ((entity, angle), orig_rot) in wing_joints
+        .iter()
+        .zip(birb_state.angles.iter())
+        .zip(original_rots.iter())
+    {
+        let Ok(mut wing_joint_transform) = transform_query.get_mut(*entity) else {
+            continue;
+        };
+        let wind_force: Quat =
+            calculate_turbulence_rotation(&wind, wing_joint_transform.translation);
+        let rot = &mut wing_joint_transform.rotation;
+        *rot = wind_force * *orig_rot * Quat::from_rotation_x(*angle);
     }
 }
 
 fn birb_inputs(
     time: Res<Time>,
-    keyboard_input: Res<Input<KeyCode>>,
+    actions: Res<Input<InputAction>>,
+    analog_actions: Res<Axis<InputAction>>,
     mut birb_state: ResMut<BirbState>,
+    flight_params: Res<FlightParams>,
+    control_mode: Res<ControlMode>,
     // world: &World,
     // names: Query<&Name>,
 ) {
-    for (key, angular_vel) in [
-        KeyCode::A,
-        KeyCode::S,
-        KeyCode::D,
-        KeyCode::F,
-        KeyCode::J,
-        KeyCode::K,
-        KeyCode::L,
-        KeyCode::Semicolon,
-    ]
-    .iter()
-    .rev()
-    .zip(birb_state.angular_velocity.iter_mut())
+    let birb_state = &mut *birb_state;
+    for (i, (angle, angular_vel)) in birb_state
+        .angles
+        .iter()
+        .zip(birb_state.angular_velocity.iter_mut())
+        .enumerate()
     {
-        *angular_vel += ANGULAR_ACCELERATION
-            * time.delta_seconds()
-            * if keyboard_input.pressed(*key) {
-                1.0
+        let action = InputAction::wing_joint(i);
+        let pressed = actions.pressed(action);
+        let analog = analog_actions.get(action).unwrap_or(0.0);
+        // Keys flap at full effort. Analog input (triggers, the mouse) always sets how far the
+        // joint goes down, whatever the control mode.
+        let acceleration =
+            if *control_mode == ControlMode::TargetAngle || (!pressed && analog > 0.0) {
+                let effort = if pressed { 1.0 } else { analog };
+                let target = flight_params.min_wing_angle
+                    + (flight_params.max_wing_angle - flight_params.min_wing_angle) * effort;
+                flight_params.target_stiffness * (target - angle)
+                    - flight_params.target_damping * *angular_vel
+            } else if pressed {
+                flight_params.angular_acceleration
             } else {
-                -1.0
+                -flight_params.angular_acceleration
             };
+        *angular_vel += acceleration * time.delta_seconds();
     }
 
-    birb_state.up_force = if keyboard_input.pressed(KeyCode::V) {
+    birb_state.up_force = if actions.pressed(InputAction::PitchUp) {
         1.0
-    } else if keyboard_input.pressed(KeyCode::N) {
+    } else if actions.pressed(InputAction::PitchDown) {
         -1.0
     } else {
         0.0
     };
 }
 
-const ANGULAR_ACCELERATION: f32 = 20.0;
-const MIN_ANGLE: f32 = -0.15 * PI;
-const MAX_ANGLE: f32 = 0.15 * PI;
-
 fn birb_physics_update(
     time: Res<Time>,
     mut birb_state: ResMut<BirbState>,
     mut birb: Query<(&mut ExternalForce, &GlobalTransform), With<Birb>>,
     global_transforms: Query<&GlobalTransform>,
     app_state: Res<State<AppState>>,
+    flight_params: Res<FlightParams>,
+    species: Res<SelectedSpecies>,
+    wind: Res<WindField>,
+    wing_surfaces: Res<WingSurfaces>,
+    power_ups: Res<PowerUps>,
 ) {
     let birb_state = &mut *birb_state;
     let paused = **app_state != AppState::InGame;
@@ -651,18 +726,19 @@ fn birb_physics_update(
             //
             // let rot: &mut Quat = &mut transforms.get_mut(*entity).unwrap().rotation;
             let mut new_angle = *angle + *angular_vel * time.delta_seconds();
-            if new_angle < MIN_ANGLE {
-                new_angle = MIN_ANGLE;
+            if new_angle < flight_params.min_wing_angle {
+                new_angle = flight_params.min_wing_angle;
                 *angular_vel = 0.0;
             }
-            if new_angle > MAX_ANGLE {
-                new_angle = MAX_ANGLE;
+            if new_angle > flight_params.max_wing_angle {
+                new_angle = flight_params.max_wing_angle;
                 *angular_vel = 0.0;
             }
             *angle = new_angle;
         }
-        let lengths = [1.14, 2.57, 2.24, 1.29];
-        let area = [2.0, 1.0, 0.5, 0.25];
+        let stats = species.0.stats();
+        let lengths = stats.segment_lengths;
+        let area = stats.segment_areas;
         let mut acc_vels = [0.0; 8];
         let mut acc_angles = [0.0; 8];
         for side in 0..2 {
@@ -684,7 +760,8 @@ fn birb_physics_update(
             wing_joints.iter().zip(acc_vels).zip(acc_angles).enumerate()
         {
             let wing_joint_global_transform = global_transforms.get(*wing_joint).unwrap();
-            let wind_force: Vec3 = calculate_wind_force(&time, wing_joint_global_transform) * 0.01;
+            let wind_force: Vec3 = calculate_wind_force(&wind, wing_joint_global_transform)
+                * flight_params.wind_strength;
             for (mut b, bt) in &mut birb {
                 if !paused {
                     b.apply_force_at_point(
@@ -695,23 +772,29 @@ fn birb_physics_update(
                     b.apply_force(
                         (bt.compute_transform().rotation * Vec3::new(0.0, 1.5, 1.0))
                             * if accumulated_angular_vel <= 0.0 {
-                                1.0
+                                flight_params.flap_force_recovery
                             } else {
-                                5.0
+                                flight_params.flap_force * power_ups.flap_strength()
                             }
                             * accumulated_angular_vel
                             * time.delta_seconds(),
                     );
+                    let rigid_lift = bt.compute_transform().rotation
+                        * Quat::from_rotation_z(if i >= 4 { -1.0 } else { 1.0 } * acc_angle)
+                        * Vec3::new(0.0, 1.5, -0.05);
+                    let cloth_normal = wing_surfaces.normals[i];
+                    let lift = if cloth_normal == Vec3::ZERO {
+                        rigid_lift
+                    } else {
+                        rigid_lift
+                            .lerp(cloth_normal * rigid_lift.length(), flight_params.cloth_lift)
+                    };
                     b.apply_force_at_point(
-                        (bt.compute_transform().rotation
-                            * Quat::from_rotation_z(if i >= 4 { -1.0 } else { 1.0 } * acc_angle)
-                            * Vec3::new(0.0, 1.5, -0.05))
-                            * if accumulated_angular_vel <= 0.0 {
-                                0.001
-                            } else {
-                                0.1
-                            }
-                            * accumulated_angular_vel
+                        lift * if accumulated_angular_vel <= 0.0 {
+                            flight_params.lift_force_recovery
+                        } else {
+                            flight_params.lift_force
+                        } * accumulated_angular_vel
                             * time.delta_seconds(),
                         wing_joint_global_transform.translation(),
                         bt.translation(),
@@ -727,7 +810,9 @@ fn birb_physics_update(
         for (mut b, bt) in &mut birb {
             b.apply_force_at_point(
                 // (wing_rot.rotation * Vec3::new(0.0, 0.0, -1.0))
-                bt.compute_transform().rotation * Vec3::new(0.0, -0.35, 0.0) * birb_state.up_force,
+                bt.compute_transform().rotation
+                    * Vec3::new(0.0, -flight_params.pitch_force, 0.0)
+                    * birb_state.up_force,
                 bt.translation() + bt.compute_transform().rotation * Vec3::new(0.0, 0.0, -1.0),
                 bt.translation(),
             );
@@ -736,59 +821,34 @@ fn birb_physics_update(
     // dbg!();
 }
 
-fn calculate_wind_force(time: &Res<Time>, bone: &GlobalTransform) -> Vec3 {
-    let perlin = Perlin::new(1337);
-    let time_factor = time.elapsed_seconds_f64();
-
-    let position = bone.translation();
-
-    // Use Perlin noise to generate wind force
-    let wind_force_x = perlin.get([
-        position.x as f64,
-        position.x as f64,
-        position.z as f64,
-        time_factor,
-    ]) as f32;
-    let wind_force_y = perlin.get([
-        position.x as f64 + 100.,
-        position.x as f64,
-        position.z as f64,
-        time_factor,
-    ]) as f32;
-    let wind_force_z = perlin.get([
-        position.x as f64 + 200.,
-        position.x as f64,
-        position.z as f64,
-        time_factor,
-    ]) as f32;
-    Vec3::new(wind_force_x, wind_force_y, wind_force_z)
+fn calculate_wind_force(wind: &WindField, bone: &GlobalTransform) -> Vec3 {
+    wind.sample(bone.translation())
 }
 
-fn calculate_turbulence_rotation(time: &Res<Time>, wing_position: Vec3) -> Quat {
-    let perlin = Perlin::new(1337);
-    let time_factor = time.elapsed_seconds_f64();
+fn calculate_turbulence_rotation(wind: &WindField, wing_position: Vec3) -> Quat {
+    let time_factor = wind.time();
 
     // Adjust these scales to control the intensity and frequency of the turbulence
     let scale = 3.0; // Scale for the noise to keep rotations subtle
     let time_scale = 2.0; // Scale for time to control the speed of changes
 
-    let intensity = 0.02 + perlin.get([time_factor, 0.0]) as f32 * 0.12;
+    let intensity = wind.turbulence_intensity();
 
     // Generate Perlin noise values for each axis
     let rotation_x =
-        perlin.get([wing_position.x as f64, time_factor * time_scale, 0.0]) as f32 * scale;
-    let rotation_y = perlin.get([
+        wind.noise([wing_position.x as f64, time_factor * time_scale, 0.0, 0.0]) * scale;
+    let rotation_y = wind.noise([
         wing_position.y as f64 + 100.0, // Offset to ensure different noise value
         time_factor * time_scale,
         0.0,
-    ]) as f32
-        * scale;
-    let rotation_z = perlin.get([
+        0.0,
+    ]) * scale;
+    let rotation_z = wind.noise([
         wing_position.z as f64 + 200.0, // Further offset
         time_factor * time_scale,
         0.0,
-    ]) as f32
-        * scale;
+        0.0,
+    ]) * scale;
 
     // Create quaternions for each axis and multiply them to combine the rotations
     (Quat::from_rotation_x(rotation_x)
@@ -814,6 +874,9 @@ fn respawn_birb_when_grounded(
     poop: Query<&Poop>,
     mut score_state: ResMut<ScoreState>,
     mut gamestate: ResMut<GameState>,
+    mut power_ups: ResMut<PowerUps>,
+    mut collected: EventWriter<Collected>,
+    mut respawned: EventWriter<Respawned>,
 ) {
     for Collision(a) in collision_event_reader.read() {
         if (birb.get(a.entity1).is_ok() || birb.get(a.entity2).is_ok())
@@ -825,9 +888,13 @@ fn respawn_birb_when_grounded(
                 bt.rotation = BIRB_SPAWN.rotation;
                 st.last_pos = bt.translation;
                 score_state.distance = 0.0;
+                score_state.bonus = 0.0;
+                score_state.typing = default();
                 lv.0 = Vec3::ZERO;
                 av.0 = Vec3::ZERO;
                 gamestate.waypoints_achieved_counter = 0;
+                power_ups.reset();
+                respawned.send(Respawned);
             }
         }
         if (collectibles.get(a.entity1).is_ok() || collectibles.get(a.entity2).is_ok())
@@ -842,13 +909,18 @@ fn respawn_birb_when_grounded(
             } else {
                 a.entity2
             };
+            let Ok(Collectible(kind)) = collectibles.get(collectible_entity) else {
+                continue;
+            };
 
             // Despawn the collectible
             commands.entity(collectible_entity).despawn();
 
-            // Increment the score
-            // GameState += 1;
-            gamestate.waypoints_achieved_counter += 1;
+            // Only score orbs count towards the score, the rest are power-ups.
+            if *kind == CollectibleKind::ScoreOrb {
+                gamestate.waypoints_achieved_counter += 1;
+            }
+            collected.send(Collected(*kind));
         }
     }
 }
@@ -0,0 +1,248 @@
+use std::time::Duration;
+
+use bevy::{
+    animation::animation_player, gltf::Gltf, prelude::*, transform::TransformSystem, utils::HashMap,
+};
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{plugins::species::SelectedSpecies, AppState, Birb, BirbState};
+
+/// Plays the authored animation clips of the birb's model and layers the procedural wing
+/// angles on top of them.
+///
+/// Models without clips are left to the procedural animation alone. The clips of `birb2.gltf`
+/// only move the wing joints, so the head and feet stay with the `SecondaryPlugin`.
+pub(crate) struct BirbAnimationPlugin;
+
+impl Plugin for BirbAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ProceduralRotations>()
+            .add_systems(OnExit(AppState::SelectSpecies), load_clips)
+            .add_systems(Update, (bind_animation_player, choose_clip).chain())
+            .add_systems(
+                PostUpdate,
+                (
+                    stash_procedural.before(animation_player),
+                    blend_procedural
+                        .after(animation_player)
+                        .before(TransformSystem::TransformPropagate),
+                )
+                    .run_if(any_with_component::<BirbAnimations>()),
+            );
+    }
+}
+
+/// The clips a model can have, looked up by their glTF animation names.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub(crate) enum BirbClip {
+    Idle,
+    Perch,
+    LandingFlare,
+}
+
+impl BirbClip {
+    const ALL: [BirbClip; 3] = [BirbClip::Idle, BirbClip::Perch, BirbClip::LandingFlare];
+
+    fn gltf_name(self) -> &'static str {
+        match self {
+            BirbClip::Idle => "Idle",
+            BirbClip::Perch => "Perch",
+            BirbClip::LandingFlare => "LandingFlare",
+        }
+    }
+
+    /// How the procedural wing angles are combined with this clip.
+    fn blend(self) -> ClipBlend {
+        match self {
+            BirbClip::Idle => ClipBlend {
+                mode: BlendMode::Layer,
+                weights: [1.0; 8],
+            },
+            // Perched wings stay folded, whatever the player does.
+            BirbClip::Perch => ClipBlend {
+                mode: BlendMode::Replace,
+                weights: [0.0; 8],
+            },
+            // The tips can still be steered while the inner wing flares.
+            BirbClip::LandingFlare => ClipBlend {
+                mode: BlendMode::Layer,
+                weights: [0.3, 0.5, 0.2, 0.0, 0.0, 0.2, 0.5, 0.3],
+            },
+        }
+    }
+}
+
+/// Speed under which a birb counts as sitting still.
+const PERCH_SPEED: f32 = 0.5;
+/// Speed under which a sinking birb flares its wings to land.
+const FLARE_SPEED: f32 = 6.0;
+const CLIP_TRANSITION: Duration = Duration::from_millis(300);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum BlendMode {
+    /// The procedural rotation is added on top of the clip.
+    Layer,
+    /// The procedural rotation replaces the clip.
+    Replace,
+}
+
+/// How much of the procedural wing animation ends up on every joint of a birb.
+#[derive(Component, Clone, PartialEq, Debug)]
+pub(crate) struct ClipBlend {
+    pub(crate) mode: BlendMode,
+    /// From 0 (only the clip) to 1 (all procedural), in `BirbState::angles` order.
+    pub(crate) weights: [f32; 8],
+}
+
+#[derive(Resource)]
+struct ModelGltf(Handle<Gltf>);
+
+/// The animation player of the birb's model and its clips, once both are there.
+#[derive(Component)]
+struct BirbAnimations {
+    player: Entity,
+    clips: HashMap<BirbClip, Handle<AnimationClip>>,
+    /// The clip being played, if the model has it.
+    playing: Option<BirbClip>,
+}
+
+/// Wing rotations set by `joint_animation`, saved before the clips overwrite them.
+#[derive(Resource, Default)]
+struct ProceduralRotations(Vec<Quat>);
+
+fn load_clips(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    species: Res<SelectedSpecies>,
+) {
+    let scene = species.0.stats().scene;
+    let path = scene.split('#').next().unwrap_or(scene);
+    commands.insert_resource(ModelGltf(asset_server.load(path.to_string())));
+}
+
+fn bind_animation_player(
+    mut commands: Commands,
+    birbs: Query<Entity, (With<Birb>, Without<BirbAnimations>)>,
+    children: Query<&Children>,
+    players: Query<(), With<AnimationPlayer>>,
+    model: Option<Res<ModelGltf>>,
+    gltfs: Res<Assets<Gltf>>,
+) {
+    let Some(gltf) = model.and_then(|model| gltfs.get(&model.0)) else {
+        return;
+    };
+    for birb in &birbs {
+        // Only models with animations get a player.
+        let Some(player) = children
+            .iter_descendants(birb)
+            .find(|e| players.contains(*e))
+        else {
+            continue;
+        };
+        let clips: HashMap<_, _> = BirbClip::ALL
+            .into_iter()
+            .filter_map(|clip| {
+                let handle = gltf.named_animations.get(clip.gltf_name())?;
+                Some((clip, handle.clone()))
+            })
+            .collect();
+        info!("Birb model has the animation clips {:?}", clips.keys());
+        commands.entity(birb).insert((
+            BirbAnimations {
+                player,
+                clips,
+                playing: None,
+            },
+            BirbClip::Idle.blend(),
+        ));
+    }
+}
+
+fn choose_clip(
+    mut birbs: Query<(&mut BirbAnimations, &mut ClipBlend, &LinearVelocity), With<Birb>>,
+    mut players: Query<&mut AnimationPlayer>,
+) {
+    for (mut animations, mut blend, velocity) in &mut birbs {
+        let speed = velocity.length();
+        let wanted = if speed < PERCH_SPEED {
+            BirbClip::Perch
+        } else if speed < FLARE_SPEED && velocity.y < 0.0 {
+            BirbClip::LandingFlare
+        } else {
+            BirbClip::Idle
+        };
+        // Fall back to idling on models without the clip.
+        let Some(clip) = [wanted, BirbClip::Idle]
+            .into_iter()
+            .find(|clip| animations.clips.contains_key(clip))
+        else {
+            continue;
+        };
+        if animations.playing == Some(clip) {
+            continue;
+        }
+        let Ok(mut player) = players.get_mut(animations.player) else {
+            continue;
+        };
+        player
+            .play_with_transition(animations.clips[&clip].clone(), CLIP_TRANSITION)
+            .repeat();
+        animations.playing = Some(clip);
+        *blend = clip.blend();
+    }
+}
+
+/// Saves the procedural rotations and puts the joints back into their rest pose, so joints the
+/// clip doesn't animate don't pick up last frame's blend.
+fn stash_procedural(
+    birb_state: Res<BirbState>,
+    mut stashed: ResMut<ProceduralRotations>,
+    mut transforms: Query<&mut Transform>,
+) {
+    let (Some(wing_joints), Some(original_rots)) =
+        (&birb_state.wing_joints, &birb_state.original_rots)
+    else {
+        return;
+    };
+    stashed.0.clear();
+    for (joint, original) in wing_joints.iter().zip(original_rots) {
+        let Ok(mut transform) = transforms.get_mut(*joint) else {
+            continue;
+        };
+        stashed.0.push(transform.rotation);
+        transform.rotation = *original;
+    }
+}
+
+fn blend_procedural(
+    birb_state: Res<BirbState>,
+    stashed: Res<ProceduralRotations>,
+    birbs: Query<&ClipBlend, With<Birb>>,
+    mut transforms: Query<&mut Transform, Without<Birb>>,
+) {
+    let (Some(wing_joints), Some(original_rots), Ok(blend)) = (
+        &birb_state.wing_joints,
+        &birb_state.original_rots,
+        birbs.get_single(),
+    ) else {
+        return;
+    };
+    for (((joint, original), procedural), weight) in wing_joints
+        .iter()
+        .zip(original_rots)
+        .zip(&stashed.0)
+        .zip(blend.weights)
+    {
+        let Ok(mut transform) = transforms.get_mut(*joint) else {
+            continue;
+        };
+        let clip = transform.rotation;
+        transform.rotation = match blend.mode {
+            BlendMode::Layer => {
+                let offset = original.inverse() * *procedural;
+                clip * Quat::IDENTITY.slerp(offset, weight)
+            }
+            BlendMode::Replace => clip.slerp(*procedural, weight),
+        };
+    }
+}
@@ -0,0 +1,200 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config,
+    plugins::{
+        controls::{ControlsScreen, ControlsSet, InputAction, PendingActions},
+        replay::PlaybackSet,
+    },
+};
+
+const CONFIG_FILE: &str = "assist.ron";
+
+/// Assisted flying for players who can't coordinate eight fingers. A single key generates the
+/// flap wave a practiced player would type across the four joints of a wing.
+pub(crate) struct AssistPlugin;
+
+impl Plugin for AssistPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(config::load::<AssistMode>(CONFIG_FILE).unwrap_or_default())
+            .init_resource::<FlapWaves>()
+            .add_systems(PostStartup, setup_assist_button)
+            .add_systems(
+                PreUpdate,
+                flap_waves.in_set(ControlsSet::Override).before(PlaybackSet),
+            )
+            .add_systems(Update, (assist_button, update_assist_button).chain());
+    }
+}
+
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub(crate) enum AssistMode {
+    /// Every joint has its own key.
+    #[default]
+    Off,
+    /// One key per wing.
+    PerWing,
+    /// One key flaps both wings, two more steer by holding back one of them.
+    SingleKey,
+}
+
+impl AssistMode {
+    fn next(self) -> Self {
+        match self {
+            AssistMode::Off => AssistMode::PerWing,
+            AssistMode::PerWing => AssistMode::SingleKey,
+            AssistMode::SingleKey => AssistMode::Off,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            AssistMode::Off => "off",
+            AssistMode::PerWing => "one key per wing",
+            AssistMode::SingleKey => "single key",
+        }
+    }
+}
+
+/// Seconds into the current flap of the left and right wing, while it is flapping.
+#[derive(Resource, Default)]
+struct FlapWaves([Option<f32>; 2]);
+
+const FLAP_PERIOD: f32 = 0.6;
+const DOWNSTROKE: f32 = 0.3;
+/// How much later each joint starts its downstroke than the one closer to the body.
+const JOINT_DELAY: f32 = 0.05;
+/// Share of the downstroke the wing on the inside of a turn flaps for.
+const TURN_EFFORT: f32 = 0.4;
+
+/// Whether `joint` is pushed down at `phase` seconds into a flap whose downstroke lasts
+/// `downstroke` seconds: down in a wave from the body outwards, then back up together.
+fn wave_pressed(joint: u8, phase: f32, downstroke: f32) -> bool {
+    let local = (phase - joint as f32 * JOINT_DELAY).rem_euclid(FLAP_PERIOD);
+    local < downstroke
+}
+
+/// Turns the assisted flap actions into wing joint key presses, so the wave moves the joints
+/// exactly like a player typing it.
+fn flap_waves(
+    time: Res<Time>,
+    mode: Res<AssistMode>,
+    mut waves: ResMut<FlapWaves>,
+    mut pending: ResMut<PendingActions>,
+) {
+    let actions = &*pending;
+    let (flapping, strength) = match *mode {
+        AssistMode::Off => ([false; 2], [0.0; 2]),
+        AssistMode::PerWing => (
+            [
+                actions.pressed(InputAction::FlapLeftWing),
+                actions.pressed(InputAction::FlapRightWing),
+            ],
+            [1.0; 2],
+        ),
+        AssistMode::SingleKey => {
+            let flap = actions.pressed(InputAction::Flap);
+            let strength = |inside_turn: InputAction| {
+                if actions.pressed(inside_turn) {
+                    TURN_EFFORT
+                } else {
+                    1.0
+                }
+            };
+            (
+                [flap; 2],
+                [
+                    strength(InputAction::TurnLeft),
+                    strength(InputAction::TurnRight),
+                ],
+            )
+        }
+    };
+
+    for (side, wing_joint) in [
+        InputAction::LeftWingJoint as fn(u8) -> InputAction,
+        InputAction::RightWingJoint,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let wave = &mut waves.0[side];
+        if !flapping[side] {
+            *wave = None;
+            continue;
+        }
+        let phase = wave.map_or(0.0, |phase| phase + time.delta_seconds());
+        *wave = Some(phase);
+        for joint in 0..4 {
+            let action = wing_joint(joint);
+            if wave_pressed(joint, phase, DOWNSTROKE * strength[side]) {
+                pending.press(action);
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+struct AssistButton;
+
+fn setup_assist_button(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    screens: Query<Entity, With<ControlsScreen>>,
+) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+        font_size: 18.0,
+        color: Color::WHITE,
+    };
+    for screen in &screens {
+        commands.entity(screen).with_children(|screen| {
+            screen
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            padding: UiRect::axes(Val::Px(10.0), Val::Px(2.0)),
+                            margin: UiRect::top(Val::Px(6.0)),
+                            ..default()
+                        },
+                        background_color: Color::DARK_GRAY.into(),
+                        ..default()
+                    },
+                    AssistButton,
+                ))
+                .with_children(|b| {
+                    b.spawn(TextBundle::from_section("", text_style.clone()));
+                });
+        });
+    }
+}
+
+fn assist_button(
+    buttons: Query<&Interaction, (Changed<Interaction>, With<AssistButton>)>,
+    mut mode: ResMut<AssistMode>,
+) {
+    for interaction in &buttons {
+        if *interaction == Interaction::Pressed {
+            *mode = mode.next();
+            config::save(CONFIG_FILE, &*mode);
+        }
+    }
+}
+
+fn update_assist_button(
+    mode: Res<AssistMode>,
+    buttons: Query<&Children, With<AssistButton>>,
+    mut texts: Query<&mut Text>,
+) {
+    if !mode.is_changed() {
+        return;
+    }
+    for children in &buttons {
+        for &child in children {
+            if let Ok(mut text) = texts.get_mut(child) {
+                text.sections[0].value = format!("Assisted flying: {}", mode.label());
+            }
+        }
+    }
+}
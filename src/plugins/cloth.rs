@@ -0,0 +1,289 @@
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        view::NoFrustumCulling,
+    },
+    transform::TransformSystem,
+};
+
+use crate::{
+    plugins::{species::SelectedSpecies, tuning::FlightParams, wind::WindField},
+    AppState, Birb, BirbState,
+};
+
+/// Simulates the wing membrane and primary feathers as cloth hanging off the wing joints.
+///
+/// Every wing is a grid of particles whose first row is pinned to the joints, integrated with
+/// Verlet and held together by distance constraints. The cloth is drawn as its own mesh on top
+/// of the skinned wings and its surface normals are shared through [`WingSurfaces`].
+pub(crate) struct ClothPlugin;
+
+impl Plugin for ClothPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WingSurfaces>().add_systems(
+            PostUpdate,
+            (spawn_wing_cloth, simulate_wing_cloth)
+                .chain()
+                .after(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// Which way the cloth of every wing joint faces, in `BirbState::angles` order. Zero until the
+/// cloth has been simulated.
+#[derive(Resource, Default)]
+pub(crate) struct WingSurfaces {
+    pub(crate) normals: [Vec3; 8],
+}
+
+/// Indices into `BirbState::angles` of the joints along each wing, from the body outwards.
+const WING_COLUMNS: [[usize; 4]; 2] = [[3, 2, 1, 0], [4, 5, 6, 7]];
+/// Particles along the wing: one per joint plus the wing tip.
+const COLS: usize = 5;
+/// Particles from the leading edge back to the tips of the feathers.
+const ROWS: usize = 4;
+/// How far past the last joint the wing tip reaches, relative to the last bone.
+const TIP_EXTENSION: f32 = 0.6;
+/// Length of the feathers at every column, before scaling with the species.
+const CHORDS: [f32; COLS] = [0.9, 1.0, 1.0, 0.9, 0.7];
+const ITERATIONS: usize = 6;
+/// Fraction of the velocity lost every frame.
+const VERLET_DAMPING: f32 = 0.02;
+/// How quickly the feathers take on the velocity of the air around them.
+const AIR_DRAG: f32 = 4.0;
+/// Anchors moving further than this in one frame restart the cloth, e.g. after a respawn.
+const RESET_DISTANCE: f32 = 5.0;
+/// Longest step simulated at once, so hitches don't blow the cloth apart.
+const MAX_STEP: f32 = 1.0 / 30.0;
+const FEATHER_COLOR: Color = Color::rgb(0.92, 0.92, 0.88);
+
+#[derive(Component)]
+struct WingCloth {
+    side: usize,
+    particles: Vec<Vec3>,
+    previous: Vec<Vec3>,
+    /// Particle pairs and the distance between them at rest.
+    constraints: Vec<(usize, usize, f32)>,
+}
+
+fn particle(row: usize, col: usize) -> usize {
+    row * COLS + col
+}
+
+fn spawn_wing_cloth(
+    mut commands: Commands,
+    birb_state: Res<BirbState>,
+    cloths: Query<(), With<WingCloth>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if birb_state.wing_joints.is_none() || !cloths.is_empty() {
+        return;
+    }
+    let material = materials.add(StandardMaterial {
+        base_color: FEATHER_COLOR,
+        perceptual_roughness: 0.9,
+        double_sided: true,
+        cull_mode: None,
+        ..default()
+    });
+
+    let mut indices = Vec::new();
+    for row in 0..ROWS - 1 {
+        for col in 0..COLS - 1 {
+            let [a, b, c, d] = [
+                particle(row, col),
+                particle(row, col + 1),
+                particle(row + 1, col),
+                particle(row + 1, col + 1),
+            ]
+            .map(|i| i as u32);
+            indices.extend([a, c, b, b, c, d]);
+        }
+    }
+    let uvs: Vec<[f32; 2]> = (0..ROWS * COLS)
+        .map(|i| {
+            let (row, col) = (i / COLS, i % COLS);
+            [
+                col as f32 / (COLS - 1) as f32,
+                row as f32 / (ROWS - 1) as f32,
+            ]
+        })
+        .collect();
+
+    for side in 0..2 {
+        let mesh = Mesh::new(PrimitiveTopology::TriangleList)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.0; 3]; ROWS * COLS])
+            .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 1.0, 0.0]; ROWS * COLS])
+            .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs.clone())
+            .with_indices(Some(Indices::U32(indices.clone())));
+        commands.spawn((
+            PbrBundle {
+                mesh: meshes.add(mesh),
+                material: material.clone(),
+                ..default()
+            },
+            // The cloth is simulated in world space, so the mesh never has a valid bounding box.
+            NoFrustumCulling,
+            WingCloth {
+                side,
+                particles: Vec::new(),
+                previous: Vec::new(),
+                constraints: Vec::new(),
+            },
+        ));
+    }
+}
+
+/// Lays the feathers out straight back from the leading edge.
+fn reset_cloth(cloth: &mut WingCloth, anchors: &[Vec3; COLS], back: Vec3, scale: f32) {
+    cloth.particles = (0..ROWS * COLS)
+        .map(|i| {
+            let (row, col) = (i / COLS, i % COLS);
+            let spacing = CHORDS[col] * scale / (ROWS - 1) as f32;
+            anchors[col] + back * spacing * row as f32
+        })
+        .collect();
+    cloth.previous = cloth.particles.clone();
+
+    let mut pairs = Vec::new();
+    for row in 0..ROWS {
+        for col in 0..COLS {
+            if row + 1 < ROWS {
+                pairs.push((particle(row, col), particle(row + 1, col)));
+            }
+            // The leading edge is pinned, so it needs no constraints along the wing.
+            if row > 0 && col + 1 < COLS {
+                pairs.push((particle(row, col), particle(row, col + 1)));
+            }
+            if row + 1 < ROWS && col + 1 < COLS {
+                pairs.push((particle(row, col), particle(row + 1, col + 1)));
+                pairs.push((particle(row, col + 1), particle(row + 1, col)));
+            }
+        }
+    }
+    cloth.constraints = pairs
+        .into_iter()
+        .map(|(a, b)| (a, b, cloth.particles[a].distance(cloth.particles[b])))
+        .collect();
+}
+
+fn simulate_wing_cloth(
+    time: Res<Time>,
+    app_state: Res<State<AppState>>,
+    birb_state: Res<BirbState>,
+    flight_params: Res<FlightParams>,
+    species: Res<SelectedSpecies>,
+    wind: Res<WindField>,
+    birbs: Query<&GlobalTransform, With<Birb>>,
+    global_transforms: Query<&GlobalTransform>,
+    mut cloths: Query<(&mut WingCloth, &Handle<Mesh>)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut surfaces: ResMut<WingSurfaces>,
+) {
+    let (Some(wing_joints), Ok(birb)) = (birb_state.wing_joints.as_ref(), birbs.get_single())
+    else {
+        return;
+    };
+    let birb_rotation = birb.compute_transform().rotation;
+    let dt = time.delta_seconds().min(MAX_STEP);
+    let gravity = Vec3::NEG_Y * flight_params.gravity;
+
+    for (mut cloth, mesh) in &mut cloths {
+        let cloth = &mut *cloth;
+        let mut anchors = [Vec3::ZERO; COLS];
+        for (anchor, &joint) in anchors.iter_mut().zip(&WING_COLUMNS[cloth.side]) {
+            let Ok(transform) = global_transforms.get(wing_joints[joint]) else {
+                return;
+            };
+            *anchor = transform.translation();
+        }
+        anchors[COLS - 1] =
+            anchors[COLS - 2] + (anchors[COLS - 2] - anchors[COLS - 3]) * TIP_EXTENSION;
+
+        if cloth.particles.is_empty() || cloth.particles[0].distance(anchors[0]) > RESET_DISTANCE {
+            let scale = species.0.stats().scale;
+            reset_cloth(cloth, &anchors, birb_rotation * Vec3::NEG_Z, scale);
+        } else if **app_state == AppState::InGame && dt > 0.0 {
+            for (i, (position, previous)) in cloth
+                .particles
+                .iter_mut()
+                .zip(&mut cloth.previous)
+                .enumerate()
+            {
+                let last = *position;
+                if i < COLS {
+                    *position = anchors[i];
+                } else {
+                    let velocity = (last - *previous) / dt;
+                    let air = wind.sample(last) - velocity;
+                    let acceleration = gravity + air * AIR_DRAG;
+                    *position +=
+                        (last - *previous) * (1.0 - VERLET_DAMPING) + acceleration * dt * dt;
+                }
+                *previous = last;
+            }
+            for _ in 0..ITERATIONS {
+                for &(a, b, rest) in &cloth.constraints {
+                    let delta = cloth.particles[b] - cloth.particles[a];
+                    let distance = delta.length();
+                    if distance <= f32::EPSILON {
+                        continue;
+                    }
+                    let correction = delta * (distance - rest) / distance;
+                    match (a < COLS, b < COLS) {
+                        (true, true) => {}
+                        (true, false) => cloth.particles[b] -= correction,
+                        (false, true) => cloth.particles[a] += correction,
+                        (false, false) => {
+                            cloth.particles[a] += correction * 0.5;
+                            cloth.particles[b] -= correction * 0.5;
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut normals = vec![Vec3::ZERO; ROWS * COLS];
+        for row in 0..ROWS - 1 {
+            for col in 0..COLS - 1 {
+                let [a, b, c, d] = [
+                    particle(row, col),
+                    particle(row, col + 1),
+                    particle(row + 1, col),
+                    particle(row + 1, col + 1),
+                ];
+                let p = &cloth.particles;
+                for [i, j, k] in [[a, c, b], [b, c, d]] {
+                    let face = (p[j] - p[i]).cross(p[k] - p[i]);
+                    for vertex in [i, j, k] {
+                        normals[vertex] += face;
+                    }
+                }
+            }
+        }
+        let up = birb_rotation * Vec3::Y;
+        for (col, &joint) in WING_COLUMNS[cloth.side].iter().enumerate() {
+            let normal = (0..ROWS)
+                .map(|row| normals[particle(row, col)])
+                .sum::<Vec3>()
+                .normalize_or_zero();
+            surfaces.normals[joint] = if normal.dot(up) < 0.0 {
+                -normal
+            } else {
+                normal
+            };
+        }
+
+        if let Some(mesh) = meshes.get_mut(mesh) {
+            let positions: Vec<[f32; 3]> = cloth.particles.iter().map(|p| p.to_array()).collect();
+            let normals: Vec<[f32; 3]> = normals
+                .iter()
+                .map(|n| n.try_normalize().unwrap_or(Vec3::Y).to_array())
+                .collect();
+            mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+            mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::plugins::controls::{ControlsSet, InputAction, PendingActions};
+
+/// Switches between flapping by acceleration and steering each joint towards a target angle.
+pub(crate) struct ControlModePlugin;
+
+impl Plugin for ControlModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ControlMode>()
+            .add_systems(Startup, setup_control_mode_text)
+            .add_systems(PreUpdate, mouse_wings.in_set(ControlsSet::Gather))
+            .add_systems(Update, toggle_control_mode);
+    }
+}
+
+/// How wing input turns into joint movement.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum ControlMode {
+    /// Keys accelerate the joints down, releasing them accelerates the joints back up. Analog
+    /// input still picks a target angle, like in [`ControlMode::TargetAngle`].
+    #[default]
+    Acceleration,
+    /// Input picks an angle between the wing limits and a PD controller moves the joint there.
+    TargetAngle,
+}
+
+#[derive(Component)]
+struct ControlModeText;
+
+fn setup_control_mode_text(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "Target angle mode",
+                TextStyle {
+                    font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                    font_size: 18.0,
+                    color: Color::WHITE,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        ControlModeText,
+    ));
+}
+
+fn toggle_control_mode(
+    actions: Res<Input<InputAction>>,
+    mut mode: ResMut<ControlMode>,
+    mut texts: Query<&mut Visibility, With<ControlModeText>>,
+) {
+    if !actions.just_pressed(InputAction::ToggleControlMode) {
+        return;
+    }
+    *mode = match *mode {
+        ControlMode::Acceleration => ControlMode::TargetAngle,
+        ControlMode::TargetAngle => ControlMode::Acceleration,
+    };
+    for mut visibility in &mut texts {
+        *visibility = match *mode {
+            ControlMode::Acceleration => Visibility::Hidden,
+            ControlMode::TargetAngle => Visibility::Visible,
+        };
+    }
+}
+
+/// In target angle mode, each half of the window is a slider for the wing on that side while
+/// the left mouse button is held: the higher the cursor, the further the wing goes down.
+fn mouse_wings(
+    mode: Res<ControlMode>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    interactions: Query<&Interaction>,
+    mut pending: ResMut<PendingActions>,
+) {
+    if *mode != ControlMode::TargetAngle || !mouse_buttons.pressed(MouseButton::Left) {
+        return;
+    }
+    // Leave clicks on buttons and sliders to the UI.
+    if interactions.iter().any(|i| *i != Interaction::None) {
+        return;
+    }
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+    let effort = (1.0 - cursor.y / window.height()).clamp(0.0, 1.0);
+    let wing_joint = if cursor.x < window.width() / 2.0 {
+        InputAction::LeftWingJoint
+    } else {
+        InputAction::RightWingJoint
+    };
+    for joint in 0..4 {
+        pending.set_analog(wing_joint(joint), effort);
+    }
+}
@@ -0,0 +1,622 @@
+use bevy::{
+    input::InputSystem,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config,
+    plugins::scan_codes::{key_label, KeyLabels, HOME_ROW},
+};
+
+const CONFIG_FILE: &str = "controls.ron";
+
+/// Maps physical inputs to [`InputAction`]s.
+///
+/// Gameplay systems only ever read `Input<InputAction>` and `Axis<InputAction>`. Every input
+/// source adds to the [`PendingActions`] in [`ControlsSet::Gather`], which are committed once at
+/// the start of every frame.
+pub(crate) struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(config::load::<InputBindings>(CONFIG_FILE).unwrap_or_default())
+            .init_resource::<Input<InputAction>>()
+            .init_resource::<Axis<InputAction>>()
+            .init_resource::<PendingActions>()
+            .init_resource::<Rebinding>()
+            .configure_sets(
+                PreUpdate,
+                (
+                    ControlsSet::Gather,
+                    ControlsSet::Override,
+                    ControlsSet::Commit,
+                    ControlsSet::Derive,
+                )
+                    .chain()
+                    .after(InputSystem),
+            )
+            .add_systems(PreUpdate, read_bindings.in_set(ControlsSet::Gather))
+            .add_systems(PreUpdate, commit_actions.in_set(ControlsSet::Commit))
+            .add_systems(Startup, setup_controls_screen)
+            .add_systems(
+                Update,
+                (
+                    toggle_controls_screen,
+                    controls_buttons,
+                    capture_rebinding,
+                    update_controls_screen,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Everything the player can do, independent of which key does it.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub(crate) enum InputAction {
+    /// Joint `n` of the left wing, counted from the body outwards.
+    LeftWingJoint(u8),
+    /// Joint `n` of the right wing, counted from the body outwards.
+    RightWingJoint(u8),
+    PitchUp,
+    PitchDown,
+    /// Assisted flap wave over the whole left wing.
+    FlapLeftWing,
+    /// Assisted flap wave over the whole right wing.
+    FlapRightWing,
+    /// Assisted flap wave over both wings.
+    Flap,
+    TurnLeft,
+    TurnRight,
+    Poop,
+    Pause,
+    ToggleTuning,
+    ToggleControlMode,
+    SaveReplay,
+    ToggleBirbVisibility,
+    DisableBirbPhysics,
+    Controls,
+    ToggleHud,
+    ToggleTyping,
+}
+
+impl InputAction {
+    pub(crate) const ALL: [InputAction; 25] = [
+        InputAction::LeftWingJoint(0),
+        InputAction::LeftWingJoint(1),
+        InputAction::LeftWingJoint(2),
+        InputAction::LeftWingJoint(3),
+        InputAction::RightWingJoint(0),
+        InputAction::RightWingJoint(1),
+        InputAction::RightWingJoint(2),
+        InputAction::RightWingJoint(3),
+        InputAction::PitchUp,
+        InputAction::PitchDown,
+        InputAction::FlapLeftWing,
+        InputAction::FlapRightWing,
+        InputAction::Flap,
+        InputAction::TurnLeft,
+        InputAction::TurnRight,
+        InputAction::Poop,
+        InputAction::Pause,
+        InputAction::ToggleTuning,
+        InputAction::ToggleControlMode,
+        InputAction::SaveReplay,
+        InputAction::ToggleBirbVisibility,
+        InputAction::DisableBirbPhysics,
+        InputAction::Controls,
+        InputAction::ToggleHud,
+        InputAction::ToggleTyping,
+    ];
+
+    /// The action flapping the joint at `index` in `BirbState::angles`.
+    ///
+    /// `BirbState` lists the joints of the birb's right wing (as seen by the player) first,
+    /// from the tip inwards, followed by the left wing from the body outwards.
+    pub(crate) fn wing_joint(index: usize) -> InputAction {
+        if index < 4 {
+            InputAction::RightWingJoint(3 - index as u8)
+        } else {
+            InputAction::LeftWingJoint(index as u8 - 4)
+        }
+    }
+
+    /// The inverse of [`InputAction::wing_joint`].
+    pub(crate) fn joint_index(self) -> Option<usize> {
+        match self {
+            InputAction::RightWingJoint(n) => Some(3 - n as usize),
+            InputAction::LeftWingJoint(n) => Some(4 + n as usize),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn label(self) -> String {
+        match self {
+            InputAction::LeftWingJoint(n) => format!("Left wing {}", n + 1),
+            InputAction::RightWingJoint(n) => format!("Right wing {}", n + 1),
+            InputAction::PitchUp => "Nose up".into(),
+            InputAction::PitchDown => "Nose down".into(),
+            InputAction::FlapLeftWing => "Flap left".into(),
+            InputAction::FlapRightWing => "Flap right".into(),
+            InputAction::Flap => "Flap".into(),
+            InputAction::TurnLeft => "Turn left".into(),
+            InputAction::TurnRight => "Turn right".into(),
+            InputAction::Poop => "Poop".into(),
+            InputAction::Pause => "Pause".into(),
+            InputAction::ToggleTuning => "Tuning panel".into(),
+            InputAction::ToggleControlMode => "Control mode".into(),
+            InputAction::SaveReplay => "Save replay".into(),
+            InputAction::ToggleBirbVisibility => "Toggle birb".into(),
+            InputAction::DisableBirbPhysics => "Freeze birb".into(),
+            InputAction::Controls => "Controls".into(),
+            InputAction::ToggleHud => "Wing display".into(),
+            InputAction::ToggleTyping => "Typing mode".into(),
+        }
+    }
+
+    /// Actions whose key should stay at the same physical position on every layout.
+    fn is_positional(self) -> bool {
+        matches!(
+            self,
+            InputAction::LeftWingJoint(_)
+                | InputAction::RightWingJoint(_)
+                | InputAction::PitchUp
+                | InputAction::PitchDown
+        )
+    }
+}
+
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+pub(crate) enum ControlsSet {
+    /// Input sources write into [`PendingActions`].
+    Gather,
+    /// Replaces everything the input sources gathered, e.g. with the inputs of a replay.
+    Override,
+    /// [`PendingActions`] are turned into `Input<InputAction>` and `Axis<InputAction>`.
+    Commit,
+    /// Actions derived from the committed ones, like the assisted flap waves.
+    Derive,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub(crate) enum Binding {
+    /// The key producing this key code on the current layout.
+    Key(KeyCode),
+    /// The key at this physical position, whatever the layout.
+    ScanCode(u32),
+    /// The button on any connected gamepad.
+    GamepadButton(GamepadButtonType),
+}
+
+impl Binding {
+    pub(crate) fn label(self, key_labels: &KeyLabels) -> String {
+        match self {
+            Binding::Key(key) => key_label(key),
+            Binding::ScanCode(scan_code) => key_labels.label(scan_code),
+            Binding::GamepadButton(button) => format!("Pad {button:?}"),
+        }
+    }
+
+    pub(crate) fn is_keyboard(self) -> bool {
+        matches!(self, Binding::Key(_) | Binding::ScanCode(_))
+    }
+}
+
+#[derive(Resource, Clone, Serialize, Deserialize)]
+pub(crate) struct InputBindings {
+    pub(crate) bindings: HashMap<InputAction, Vec<Binding>>,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        // Same order as `HOME_ROW`.
+        let home_row = [
+            InputAction::LeftWingJoint(3),
+            InputAction::LeftWingJoint(2),
+            InputAction::LeftWingJoint(1),
+            InputAction::LeftWingJoint(0),
+            InputAction::RightWingJoint(0),
+            InputAction::RightWingJoint(1),
+            InputAction::RightWingJoint(2),
+            InputAction::RightWingJoint(3),
+            InputAction::PitchUp,
+            InputAction::PitchDown,
+        ];
+        let home_row_keys = [
+            KeyCode::A,
+            KeyCode::S,
+            KeyCode::D,
+            KeyCode::F,
+            KeyCode::J,
+            KeyCode::K,
+            KeyCode::L,
+            KeyCode::Semicolon,
+            KeyCode::V,
+            KeyCode::N,
+        ];
+        let home_row_bindings = match HOME_ROW {
+            Some(scan_codes) => scan_codes.map(|(scan_code, _)| Binding::ScanCode(scan_code)),
+            None => home_row_keys.map(Binding::Key),
+        };
+
+        let bindings = [
+            (InputAction::FlapLeftWing, KeyCode::ShiftLeft),
+            (InputAction::FlapRightWing, KeyCode::ShiftRight),
+            (InputAction::Flap, KeyCode::B),
+            (InputAction::TurnLeft, KeyCode::Z),
+            (InputAction::TurnRight, KeyCode::X),
+            (InputAction::Poop, KeyCode::Space),
+            (InputAction::Pause, KeyCode::Escape),
+            (InputAction::SaveReplay, KeyCode::F5),
+            (InputAction::ToggleControlMode, KeyCode::F6),
+            (InputAction::ToggleTuning, KeyCode::F7),
+            (InputAction::ToggleBirbVisibility, KeyCode::F8),
+            (InputAction::DisableBirbPhysics, KeyCode::F9),
+            (InputAction::Controls, KeyCode::F10),
+            (InputAction::ToggleHud, KeyCode::F4),
+            (InputAction::ToggleTyping, KeyCode::F3),
+        ]
+        .into_iter()
+        .map(|(action, key)| (action, Binding::Key(key)))
+        .chain(home_row.into_iter().zip(home_row_bindings))
+        .map(|(action, binding)| (action, vec![binding]))
+        .collect::<HashMap<_, _>>();
+        let mut bindings = Self { bindings };
+        for (action, button) in [
+            (InputAction::PitchUp, GamepadButtonType::DPadUp),
+            (InputAction::PitchDown, GamepadButtonType::DPadDown),
+            (InputAction::Poop, GamepadButtonType::South),
+            (InputAction::Pause, GamepadButtonType::East),
+            (InputAction::Pause, GamepadButtonType::Start),
+        ] {
+            bindings
+                .bindings
+                .entry(action)
+                .or_default()
+                .push(Binding::GamepadButton(button));
+        }
+        bindings
+    }
+}
+
+impl InputBindings {
+    pub(crate) fn get(&self, action: InputAction) -> &[Binding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Replaces the binding of `action` with `binding`, keeping bindings of other devices.
+    /// Actions already bound to the same key or button, given as all `same_input` bindings that
+    /// refer to it, get the replaced binding instead, so no key fires two actions.
+    fn rebind(&mut self, action: InputAction, binding: Binding, same_input: &[Binding]) {
+        let bindings = self.bindings.entry(action).or_default();
+        let replaced = bindings
+            .iter()
+            .find(|b| b.is_keyboard() == binding.is_keyboard() && !same_input.contains(b))
+            .copied();
+        bindings.retain(|b| b.is_keyboard() != binding.is_keyboard());
+        bindings.insert(0, binding);
+
+        for (_, bindings) in self.bindings.iter_mut().filter(|(a, _)| **a != action) {
+            let Some(i) = bindings.iter().position(|b| same_input.contains(b)) else {
+                continue;
+            };
+            bindings.retain(|b| !same_input.contains(b));
+            if let Some(replaced) = replaced.filter(|b| !bindings.contains(b)) {
+                bindings.insert(i.min(bindings.len()), replaced);
+            }
+        }
+    }
+}
+
+/// Actions requested by the input sources during [`ControlsSet::Gather`].
+#[derive(Resource, Default)]
+pub(crate) struct PendingActions {
+    pressed: HashSet<InputAction>,
+    analog: HashMap<InputAction, f32>,
+}
+
+impl PendingActions {
+    pub(crate) fn press(&mut self, action: InputAction) {
+        self.pressed.insert(action);
+    }
+
+    pub(crate) fn pressed(&self, action: InputAction) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    /// Drops whatever the input sources requested for `action` so far.
+    pub(crate) fn release(&mut self, action: InputAction) {
+        self.pressed.remove(&action);
+        self.analog.remove(&action);
+    }
+
+    /// Sets an analog value in `0.0..=1.0`. When several sources drive the same action the
+    /// strongest one wins.
+    pub(crate) fn set_analog(&mut self, action: InputAction, value: f32) {
+        let current = self.analog.entry(action).or_default();
+        *current = current.max(value.clamp(0.0, 1.0));
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.pressed.clear();
+        self.analog.clear();
+    }
+}
+
+pub(crate) fn read_bindings(
+    keys: Res<Input<KeyCode>>,
+    scan_codes: Res<Input<ScanCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    bindings: Res<InputBindings>,
+    mut pending: ResMut<PendingActions>,
+) {
+    for action in InputAction::ALL {
+        let pressed = bindings.get(action).iter().any(|binding| match *binding {
+            Binding::Key(key) => keys.pressed(key),
+            Binding::ScanCode(scan_code) => scan_codes.pressed(ScanCode(scan_code)),
+            Binding::GamepadButton(button_type) => gamepads
+                .iter()
+                .any(|gamepad| gamepad_buttons.pressed(GamepadButton::new(gamepad, button_type))),
+        });
+        if pressed {
+            pending.press(action);
+        }
+    }
+}
+
+pub(crate) fn commit_actions(
+    rebinding: Res<Rebinding>,
+    mut pending: ResMut<PendingActions>,
+    mut actions: ResMut<Input<InputAction>>,
+    mut axes: ResMut<Axis<InputAction>>,
+) {
+    actions.clear();
+    for action in InputAction::ALL {
+        // Don't fly around while the player is choosing a new key.
+        if rebinding.0.is_none() && pending.pressed.contains(&action) {
+            actions.press(action);
+        } else {
+            actions.release(action);
+        }
+        let analog = match rebinding.0 {
+            None => pending.analog.get(&action).copied().unwrap_or(0.0),
+            Some(_) => 0.0,
+        };
+        axes.set(action, analog);
+    }
+    pending.clear();
+}
+
+/// The action waiting for a new key on the controls screen.
+#[derive(Resource, Default)]
+pub(crate) struct Rebinding(Option<InputAction>);
+
+#[derive(Component)]
+pub(crate) struct ControlsScreen;
+
+#[derive(Component)]
+enum ControlsButton {
+    Rebind(InputAction),
+    Reset,
+}
+
+#[derive(Component)]
+struct BindingText(InputAction);
+
+const FONT_SIZE: f32 = 18.0;
+
+fn setup_controls_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+    let text_style = TextStyle {
+        font,
+        font_size: FONT_SIZE,
+        color: Color::WHITE,
+    };
+    let button_style = Style {
+        padding: UiRect::axes(Val::Px(10.0), Val::Px(2.0)),
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(50.0),
+                    top: Val::Px(10.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.0),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.75).into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            ControlsScreen,
+        ))
+        .with_children(|screen| {
+            screen.spawn(TextBundle::from_section(
+                "Click an action, then press its new key or button",
+                text_style.clone(),
+            ));
+            for action in InputAction::ALL {
+                screen
+                    .spawn((
+                        ButtonBundle {
+                            style: button_style.clone(),
+                            background_color: Color::DARK_GRAY.into(),
+                            ..default()
+                        },
+                        ControlsButton::Rebind(action),
+                    ))
+                    .with_children(|b| {
+                        b.spawn((
+                            TextBundle::from_section("", text_style.clone()),
+                            BindingText(action),
+                        ));
+                    });
+            }
+            screen
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            margin: UiRect::top(Val::Px(6.0)),
+                            ..button_style.clone()
+                        },
+                        background_color: Color::DARK_GRAY.into(),
+                        ..default()
+                    },
+                    ControlsButton::Reset,
+                ))
+                .with_children(|b| {
+                    b.spawn(TextBundle::from_section(
+                        "Reset to defaults",
+                        text_style.clone(),
+                    ));
+                });
+        });
+}
+
+fn toggle_controls_screen(
+    actions: Res<Input<InputAction>>,
+    mut screen: Query<&mut Visibility, With<ControlsScreen>>,
+) {
+    if actions.just_pressed(InputAction::Controls) {
+        for mut visibility in &mut screen {
+            *visibility = match *visibility {
+                Visibility::Hidden => Visibility::Visible,
+                Visibility::Visible | Visibility::Inherited => Visibility::Hidden,
+            };
+        }
+    }
+}
+
+fn controls_buttons(
+    buttons: Query<(&Interaction, &ControlsButton), Changed<Interaction>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<InputBindings>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button {
+            ControlsButton::Rebind(action) => rebinding.0 = Some(*action),
+            ControlsButton::Reset => {
+                *bindings = InputBindings::default();
+                config::save(CONFIG_FILE, &*bindings);
+            }
+        }
+    }
+}
+
+fn capture_rebinding(
+    keys: Res<Input<KeyCode>>,
+    scan_codes: Res<Input<ScanCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<InputBindings>,
+) {
+    let Some(action) = rebinding.0 else {
+        return;
+    };
+    if keys.just_pressed(KeyCode::Escape) {
+        rebinding.0 = None;
+        return;
+    }
+    // The same key press, by layout and by position.
+    let key = keys.get_just_pressed().next().map(|&key| Binding::Key(key));
+    let scan_code = scan_codes
+        .get_just_pressed()
+        .next()
+        .map(|scan_code| Binding::ScanCode(scan_code.0));
+    // Flying keys are remembered by position, so they keep working when the layout changes.
+    let use_scan_code = action.is_positional() && HOME_ROW.is_some();
+    let binding = if use_scan_code { scan_code } else { key }.or_else(|| {
+        gamepad_buttons
+            .get_just_pressed()
+            .next()
+            .map(|button| Binding::GamepadButton(button.button_type))
+    });
+    let Some(binding) = binding else {
+        return;
+    };
+    let same_input: Vec<Binding> = [Some(binding), key, scan_code]
+        .into_iter()
+        .flatten()
+        .filter(|b| b.is_keyboard() == binding.is_keyboard())
+        .collect();
+    rebinding.0 = None;
+    bindings.rebind(action, binding, &same_input);
+    config::save(CONFIG_FILE, &*bindings);
+}
+
+fn update_controls_screen(
+    bindings: Res<InputBindings>,
+    rebinding: Res<Rebinding>,
+    key_labels: Res<KeyLabels>,
+    mut texts: Query<(&mut Text, &BindingText)>,
+) {
+    if !bindings.is_changed() && !rebinding.is_changed() && !key_labels.is_changed() {
+        return;
+    }
+    for (mut text, binding_text) in &mut texts {
+        let action = binding_text.0;
+        let keys = if rebinding.0 == Some(action) {
+            "press a key, Esc to cancel".to_string()
+        } else {
+            bindings
+                .get(action)
+                .iter()
+                .map(|b| b.label(&key_labels))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        text.sections[0].value = format!("{:<14} {keys}", action.label());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebinding_to_a_used_key_swaps_the_bindings() {
+        let mut bindings = InputBindings::default();
+        bindings.rebind(InputAction::Poop, Binding::Key(KeyCode::P), &[]);
+        bindings.rebind(
+            InputAction::Pause,
+            Binding::Key(KeyCode::P),
+            &[Binding::Key(KeyCode::P)],
+        );
+        assert_eq!(
+            bindings.get(InputAction::Pause)[0],
+            Binding::Key(KeyCode::P)
+        );
+        assert!(bindings
+            .get(InputAction::Poop)
+            .contains(&Binding::Key(KeyCode::Escape)));
+        assert!(!bindings
+            .get(InputAction::Poop)
+            .contains(&Binding::Key(KeyCode::P)));
+    }
+
+    #[test]
+    fn positional_and_layout_bindings_of_one_key_conflict() {
+        let mut bindings = InputBindings::default();
+        bindings.rebind(InputAction::Poop, Binding::Key(KeyCode::Q), &[]);
+        bindings.rebind(
+            InputAction::LeftWingJoint(3),
+            Binding::ScanCode(16),
+            &[Binding::ScanCode(16), Binding::Key(KeyCode::Q)],
+        );
+        assert!(bindings
+            .get(InputAction::Poop)
+            .iter()
+            .all(|b| *b != Binding::Key(KeyCode::Q)));
+    }
+}
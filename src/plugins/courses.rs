@@ -0,0 +1,678 @@
+use bevy::{
+    asset::{
+        io::Reader, AssetLoader, AsyncReadExt, LoadContext, LoadState, RecursiveDependencyLoadState,
+    },
+    prelude::*,
+    utils::BoxedFuture,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{config, AppState, Birb, Respawned, BIRB_SPAWN};
+
+#[cfg(not(target_arch = "wasm32"))]
+const COURSE_DIR: &str = "courses";
+#[cfg(target_arch = "wasm32")]
+const COURSE_LIST: &str = "courses/web.courses.ron";
+const CONFIG_FILE: &str = "course.ron";
+
+/// Race courses of rings that have to be flown through in order, loaded from the
+/// `*.course.ron` files in `assets/courses/`.
+pub(crate) struct CoursesPlugin;
+
+impl Plugin for CoursesPlugin {
+    fn build(&self, app: &mut App) {
+        // A replay brings its own course.
+        if !app.world.contains_resource::<SelectedCourse>() {
+            app.insert_resource(config::load::<SelectedCourse>(CONFIG_FILE).unwrap_or_default());
+        }
+
+        app.init_asset::<Course>()
+            .init_asset::<CourseList>()
+            .register_asset_loader(CourseLoader)
+            .register_asset_loader(CourseListLoader)
+            .init_resource::<Courses>()
+            .add_systems(Startup, load_courses)
+            .add_systems(
+                PreUpdate,
+                (collect_courses, hold_until_loaded)
+                    .chain()
+                    .run_if(in_state(AppState::SelectSpecies)),
+            )
+            .add_systems(OnEnter(AppState::SelectSpecies), setup_course_panel)
+            .add_systems(
+                OnExit(AppState::SelectSpecies),
+                (despawn_course_panel, start_course),
+            )
+            .add_systems(
+                Update,
+                (
+                    (
+                        (despawn_course_panel, setup_course_panel)
+                            .chain()
+                            .run_if(resource_changed::<Courses>()),
+                        course_buttons,
+                        time_trial_button,
+                        update_course_buttons,
+                    )
+                        .chain()
+                        .run_if(in_state(AppState::SelectSpecies)),
+                    (restart_course, pass_rings, update_rings, guidance_arrow)
+                        .chain()
+                        .run_if(in_state(AppState::InGame)),
+                    update_course_text,
+                ),
+            );
+    }
+}
+
+#[derive(Asset, TypePath, Deserialize, Clone)]
+pub(crate) struct Course {
+    pub(crate) name: String,
+    /// Seed of the world in time trials, derived from the name if left out.
+    #[serde(default)]
+    seed: Option<u64>,
+    pub(crate) rings: Vec<Ring>,
+}
+
+impl Course {
+    pub(crate) fn seed(&self) -> u64 {
+        // FNV-1a, so the seed stays the same across builds.
+        self.seed.unwrap_or_else(|| {
+            self.name.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+            })
+        })
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub(crate) struct Ring {
+    pub(crate) position: Vec3,
+    #[serde(default = "Ring::default_radius")]
+    pub(crate) radius: f32,
+}
+
+impl Ring {
+    fn default_radius() -> f32 {
+        10.0
+    }
+}
+
+/// The course files listed for the web build, which can't read directories.
+#[derive(Asset, TypePath)]
+struct CourseList(#[dependency] Vec<Handle<Course>>);
+
+fn read_ron<'a, T: DeserializeOwned>(
+    reader: &'a mut Reader,
+) -> BoxedFuture<'a, Result<T, Box<dyn std::error::Error + Send + Sync>>> {
+    Box::pin(async move {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    })
+}
+
+struct CourseLoader;
+
+impl AssetLoader for CourseLoader {
+    type Asset = Course;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Course, Self::Error>> {
+        read_ron(reader)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["course.ron"]
+    }
+}
+
+struct CourseListLoader;
+
+impl AssetLoader for CourseListLoader {
+    type Asset = CourseList;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<CourseList, Self::Error>> {
+        Box::pin(async move {
+            let files: Vec<String> = read_ron(reader).await?;
+            // The files are named relative to the list.
+            let dir = load_context.path().parent().map(|p| p.to_owned());
+            let courses = files
+                .into_iter()
+                .map(|file| load_context.load(dir.clone().unwrap_or_default().join(file)))
+                .collect();
+            Ok(CourseList(courses))
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["courses.ron"]
+    }
+}
+
+/// Every course in `assets/courses/`, with the name of its file.
+#[derive(Resource, Default)]
+pub(crate) struct Courses {
+    courses: Vec<(String, Course)>,
+    /// Keeps the course files loaded.
+    files: Option<UntypedHandle>,
+    /// Whether every course file has been loaded or has failed to.
+    loaded: bool,
+}
+
+impl Courses {
+    pub(crate) fn get(&self, file: &str) -> Option<&Course> {
+        self.courses.iter().find(|(f, _)| f == file).map(|(_, c)| c)
+    }
+}
+
+#[derive(Resource, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct SelectedCourse {
+    /// File name of the course to fly, or `None` for free flight.
+    pub(crate) file: Option<String>,
+    /// Whether the course is flown against the best time, in a world seeded by the course.
+    pub(crate) time_trial: bool,
+}
+
+impl SelectedCourse {
+    /// The course flown as a time trial, with its file name.
+    pub(crate) fn time_trial<'a>(&'a self, courses: &'a Courses) -> Option<(&'a str, &'a Course)> {
+        let file = self.file.as_deref().filter(|_| self.time_trial)?;
+        Some((file, courses.get(file)?))
+    }
+}
+
+/// Progress through the course being flown.
+#[derive(Resource)]
+pub(crate) struct CourseRun {
+    pub(crate) course: Course,
+    /// Direction each ring has to be flown through.
+    normals: Vec<Vec3>,
+    /// Index of the ring to fly through next, the number of rings once finished.
+    pub(crate) next: usize,
+    /// Seconds since the start.
+    pub(crate) elapsed: f32,
+    /// Time at which every passed ring was flown through.
+    pub(crate) splits: Vec<f32>,
+    last_position: Option<Vec3>,
+}
+
+impl CourseRun {
+    pub(crate) fn is_finished(&self) -> bool {
+        self.next >= self.course.rings.len()
+    }
+
+    fn restart(&mut self) {
+        self.next = 0;
+        self.elapsed = 0.0;
+        self.splits.clear();
+        self.last_position = None;
+    }
+}
+
+#[derive(Component)]
+struct CourseRing(usize);
+
+#[derive(Resource)]
+struct RingMaterials {
+    next: Handle<StandardMaterial>,
+    finish: Handle<StandardMaterial>,
+    upcoming: Handle<StandardMaterial>,
+}
+
+#[derive(Component)]
+struct CoursePanel;
+
+#[derive(Component)]
+struct CourseButton(Option<String>);
+
+#[derive(Component)]
+struct TimeTrialButton;
+
+#[derive(Component)]
+struct CourseText;
+
+const RING_THICKNESS: f32 = 0.6;
+const ARROW_LENGTH: f32 = 3.0;
+/// Height of the guidance arrow above the birb.
+const ARROW_HEIGHT: f32 = 2.5;
+const NEXT_COLOR: Color = Color::rgb(1.0, 0.843, 0.0);
+const FINISH_COLOR: Color = Color::rgb(1.0, 0.25, 0.25);
+const UPCOMING_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.3);
+const SELECTED_COLOR: Color = Color::rgb(0.35, 0.35, 0.2);
+
+fn load_courses(asset_server: Res<AssetServer>, mut courses: ResMut<Courses>) {
+    #[cfg(not(target_arch = "wasm32"))]
+    let files = asset_server.load_folder(COURSE_DIR).untyped();
+    #[cfg(target_arch = "wasm32")]
+    let files = asset_server.load::<CourseList>(COURSE_LIST).untyped();
+    courses.files = Some(files);
+}
+
+fn collect_courses(
+    asset_server: Res<AssetServer>,
+    assets: Res<Assets<Course>>,
+    mut courses: ResMut<Courses>,
+    mut selected: ResMut<SelectedCourse>,
+) {
+    let Some(files) = courses.files.as_ref().map(|f| f.id()) else {
+        return;
+    };
+    if courses.loaded {
+        return;
+    }
+    // A missing folder or a broken course shouldn't keep the game from starting.
+    let loaded = match asset_server.load_state(files) {
+        LoadState::Failed => true,
+        LoadState::Loaded => matches!(
+            asset_server.recursive_dependency_load_state(files),
+            RecursiveDependencyLoadState::Loaded | RecursiveDependencyLoadState::Failed
+        ),
+        _ => false,
+    };
+    if !loaded {
+        return;
+    }
+
+    let mut loaded: Vec<(String, Course)> = assets
+        .iter()
+        .filter_map(|(id, course)| {
+            let path = asset_server.get_path(id)?;
+            let file = path.path().file_name()?.to_str()?.to_string();
+            Some((file, course.clone()))
+        })
+        .collect();
+    loaded.sort_by(|a, b| a.0.cmp(&b.0));
+    info!("Loaded {} courses", loaded.len());
+    courses.courses = loaded;
+    courses.loaded = true;
+
+    // Forget courses that have been removed since.
+    if let Some(file) = &selected.file {
+        if courses.get(file).is_none() {
+            selected.file = None;
+        }
+    }
+}
+
+/// Keeps the species screen up until the courses have loaded, so that the run (or a replay,
+/// which skips the screen) starts with the selected course.
+fn hold_until_loaded(
+    courses: Res<Courses>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut held: Local<Option<AppState>>,
+) {
+    if let Some(next) = next_state.0.take() {
+        *held = Some(next);
+    }
+    if courses.loaded {
+        next_state.0 = held.take();
+    }
+}
+
+fn setup_course_panel(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    courses: Res<Courses>,
+) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+        font_size: 16.0,
+        color: Color::WHITE,
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(20.0),
+                    top: Val::Px(20.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.0),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+                // Above the species screen, which covers everything.
+                z_index: ZIndex::Global(1),
+                ..default()
+            },
+            CoursePanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn(TextBundle::from_section("Course", text_style.clone()));
+            let choices = std::iter::once((None, "Free flight")).chain(
+                courses
+                    .courses
+                    .iter()
+                    .map(|(file, c)| (Some(file), c.name.as_str())),
+            );
+            for (file, name) in choices {
+                panel
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                padding: UiRect::all(Val::Px(4.0)),
+                                ..default()
+                            },
+                            background_color: Color::DARK_GRAY.into(),
+                            ..default()
+                        },
+                        CourseButton(file.cloned()),
+                    ))
+                    .with_children(|b| {
+                        b.spawn(TextBundle::from_section(name, text_style.clone()));
+                    });
+            }
+            panel
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            padding: UiRect::all(Val::Px(4.0)),
+                            margin: UiRect::top(Val::Px(6.0)),
+                            ..default()
+                        },
+                        background_color: Color::DARK_GRAY.into(),
+                        ..default()
+                    },
+                    TimeTrialButton,
+                ))
+                .with_children(|b| {
+                    b.spawn(TextBundle::from_section("Time trial", text_style.clone()));
+                });
+        });
+}
+
+fn despawn_course_panel(mut commands: Commands, panels: Query<Entity, With<CoursePanel>>) {
+    for panel in &panels {
+        commands.entity(panel).despawn_recursive();
+    }
+}
+
+fn course_buttons(
+    buttons: Query<(&Interaction, &CourseButton), Changed<Interaction>>,
+    mut selected: ResMut<SelectedCourse>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction == Interaction::Pressed {
+            selected.file.clone_from(&button.0);
+            config::save(CONFIG_FILE, &*selected);
+        }
+    }
+}
+
+fn time_trial_button(
+    buttons: Query<&Interaction, (Changed<Interaction>, With<TimeTrialButton>)>,
+    mut selected: ResMut<SelectedCourse>,
+) {
+    for interaction in &buttons {
+        if *interaction == Interaction::Pressed {
+            selected.time_trial = !selected.time_trial;
+            config::save(CONFIG_FILE, &*selected);
+        }
+    }
+}
+
+fn update_course_buttons(
+    selected: Res<SelectedCourse>,
+    mut buttons: Query<(Ref<CourseButton>, &mut BackgroundColor), Without<TimeTrialButton>>,
+    mut time_trial_buttons: Query<(Ref<TimeTrialButton>, &mut BackgroundColor)>,
+) {
+    let color = |selected: bool| {
+        if selected {
+            SELECTED_COLOR
+        } else {
+            Color::DARK_GRAY
+        }
+    };
+    for (button, mut background) in &mut buttons {
+        if selected.is_changed() || button.is_added() {
+            background.0 = color(button.0 == selected.file);
+        }
+    }
+    for (button, mut background) in &mut time_trial_buttons {
+        if selected.is_changed() || button.is_added() {
+            background.0 = color(selected.time_trial);
+        }
+    }
+}
+
+fn start_course(
+    mut commands: Commands,
+    courses: Res<Courses>,
+    selected: Res<SelectedCourse>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let Some(course) = selected
+        .file
+        .as_deref()
+        .and_then(|file| courses.get(file))
+        .cloned()
+    else {
+        return;
+    };
+    if course.rings.is_empty() {
+        warn!("Course {} has no rings", course.name);
+        return;
+    }
+
+    let material = |color: Color| StandardMaterial {
+        base_color: color,
+        emissive: color * 0.5,
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    };
+    let ring_materials = RingMaterials {
+        next: materials.add(material(NEXT_COLOR)),
+        finish: materials.add(material(FINISH_COLOR)),
+        upcoming: materials.add(material(UPCOMING_COLOR)),
+    };
+
+    // Every ring faces the way from the previous one, the first from the spawn point.
+    let mut previous = BIRB_SPAWN.translation;
+    let mut normals = Vec::with_capacity(course.rings.len());
+    for (i, ring) in course.rings.iter().enumerate() {
+        let normal = (ring.position - previous)
+            .try_normalize()
+            .unwrap_or(Vec3::Z);
+        previous = ring.position;
+        normals.push(normal);
+        commands.spawn((
+            PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::Torus {
+                    radius: ring.radius,
+                    ring_radius: RING_THICKNESS,
+                    ..default()
+                })),
+                material: ring_materials.upcoming.clone(),
+                // The torus lies flat, around the Y axis.
+                transform: Transform::from_translation(ring.position)
+                    .with_rotation(Quat::from_rotation_arc(Vec3::Y, normal)),
+                ..default()
+            },
+            CourseRing(i),
+        ));
+    }
+
+    info!("Flying course {}", course.name);
+    commands.insert_resource(ring_materials);
+    commands.insert_resource(CourseRun {
+        course,
+        normals,
+        next: 0,
+        elapsed: 0.0,
+        splits: Vec::new(),
+        last_position: None,
+    });
+}
+
+fn restart_course(mut respawned: EventReader<Respawned>, run: Option<ResMut<CourseRun>>) {
+    if respawned.read().count() > 0 {
+        if let Some(mut run) = run {
+            run.restart();
+        }
+    }
+}
+
+fn pass_rings(
+    time: Res<Time>,
+    run: Option<ResMut<CourseRun>>,
+    birbs: Query<&Transform, With<Birb>>,
+) {
+    let (Some(mut run), Ok(birb)) = (run, birbs.get_single()) else {
+        return;
+    };
+    if run.is_finished() {
+        return;
+    }
+    run.elapsed += time.delta_seconds();
+    let position = birb.translation;
+    let Some(last) = run.last_position.replace(position) else {
+        return;
+    };
+
+    let ring = &run.course.rings[run.next];
+    let normal = run.normals[run.next];
+    let before = (last - ring.position).dot(normal);
+    let after = (position - ring.position).dot(normal);
+    // Only count crossing the ring's plane forwards, inside the ring.
+    if before >= 0.0 || after < 0.0 {
+        return;
+    }
+    let crossing = last + (position - last) * (before / (before - after));
+    if crossing.distance(ring.position) > ring.radius {
+        return;
+    }
+    let elapsed = run.elapsed;
+    run.splits.push(elapsed);
+    run.next += 1;
+    if run.is_finished() {
+        info!("Finished {} in {elapsed:.2}s", run.course.name);
+    }
+}
+
+fn update_rings(
+    run: Option<Res<CourseRun>>,
+    ring_materials: Option<Res<RingMaterials>>,
+    mut rings: Query<(&CourseRing, &mut Handle<StandardMaterial>, &mut Visibility)>,
+) {
+    let (Some(run), Some(ring_materials)) = (run, ring_materials) else {
+        return;
+    };
+    if !run.is_changed() {
+        return;
+    }
+    let last = run.course.rings.len() - 1;
+    for (ring, mut material, mut visibility) in &mut rings {
+        let wanted = if ring.0 < run.next {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+        let wanted = if ring.0 == run.next && ring.0 == last {
+            &ring_materials.finish
+        } else if ring.0 == run.next {
+            &ring_materials.next
+        } else {
+            &ring_materials.upcoming
+        };
+        if *material != *wanted {
+            *material = wanted.clone();
+        }
+    }
+}
+
+/// Points from above the birb towards the next ring.
+fn guidance_arrow(
+    run: Option<Res<CourseRun>>,
+    birbs: Query<&Transform, With<Birb>>,
+    mut gizmos: Gizmos,
+) {
+    let (Some(run), Ok(birb)) = (run, birbs.get_single()) else {
+        return;
+    };
+    let Some(ring) = run.course.rings.get(run.next) else {
+        return;
+    };
+    let start = birb.translation + Vec3::Y * ARROW_HEIGHT;
+    let Some(direction) = (ring.position - start).try_normalize() else {
+        return;
+    };
+    let tip = start + direction * ARROW_LENGTH;
+    let side = direction.any_orthonormal_vector() * ARROW_LENGTH * 0.25;
+    let back = direction * ARROW_LENGTH * 0.3;
+    gizmos.line(start, tip, NEXT_COLOR);
+    gizmos.line(tip, tip - back + side, NEXT_COLOR);
+    gizmos.line(tip, tip - back - side, NEXT_COLOR);
+}
+
+fn update_course_text(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    run: Option<Res<CourseRun>>,
+    mut texts: Query<&mut Text, With<CourseText>>,
+) {
+    let Some(run) = run else {
+        return;
+    };
+    let Ok(mut text) = texts.get_single_mut() else {
+        commands.spawn((
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                    font_size: 22.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(10.0),
+                top: Val::Px(10.0),
+                ..default()
+            }),
+            CourseText,
+        ));
+        return;
+    };
+    if !run.is_changed() {
+        return;
+    }
+
+    let rings = run.course.rings.len();
+    let mut value = if run.is_finished() {
+        format!("{}\nFinished in {:.2}s\n", run.course.name, run.elapsed)
+    } else {
+        format!(
+            "{}\nRing {}/{rings}  {:.2}s\n",
+            run.course.name,
+            run.next + 1,
+            run.elapsed
+        )
+    };
+    let mut previous = 0.0;
+    for (i, split) in run.splits.iter().enumerate() {
+        value += &format!("{:>2}: {split:6.2}s (+{:.2})\n", i + 1, split - previous);
+        previous = *split;
+    }
+    text.sections[0].value = value;
+}
@@ -0,0 +1,452 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{config, plugins::score::ScoreState, AppState, Birb};
+
+const CONFIG_FILE: &str = "customization.ron";
+
+/// Lets players paint their birb, with some colours and patterns unlocked by their best score.
+///
+/// The model has a single material, so the body, wings and belly are painted as vertex colours
+/// on a copy of the mesh and the material is overridden to show them.
+pub(crate) struct CustomizationPlugin;
+
+impl Plugin for CustomizationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(config::load::<Customization>(CONFIG_FILE).unwrap_or_default())
+            .add_systems(OnEnter(AppState::SelectSpecies), setup_customization_panel)
+            .add_systems(OnExit(AppState::SelectSpecies), despawn_customization_panel)
+            .add_systems(
+                Update,
+                (
+                    (customization_buttons, update_customization_buttons)
+                        .chain()
+                        .run_if(in_state(AppState::SelectSpecies)),
+                    apply_customization,
+                    unlock_by_score,
+                ),
+            );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub(crate) enum BirbColor {
+    White,
+    Grey,
+    Brown,
+    Black,
+    Blue,
+    Red,
+    Green,
+    Gold,
+}
+
+impl BirbColor {
+    const ALL: [BirbColor; 8] = [
+        BirbColor::White,
+        BirbColor::Grey,
+        BirbColor::Brown,
+        BirbColor::Black,
+        BirbColor::Blue,
+        BirbColor::Red,
+        BirbColor::Green,
+        BirbColor::Gold,
+    ];
+
+    fn color(self) -> Color {
+        match self {
+            BirbColor::White => Color::rgb(0.95, 0.95, 0.92),
+            BirbColor::Grey => Color::rgb(0.6, 0.6, 0.62),
+            BirbColor::Brown => Color::rgb(0.45, 0.3, 0.18),
+            BirbColor::Black => Color::rgb(0.12, 0.12, 0.14),
+            BirbColor::Blue => Color::rgb(0.2, 0.45, 0.85),
+            BirbColor::Red => Color::rgb(0.8, 0.15, 0.1),
+            BirbColor::Green => Color::rgb(0.2, 0.6, 0.25),
+            BirbColor::Gold => Color::rgb(0.95, 0.75, 0.2),
+        }
+    }
+
+    /// Best score needed to pick this colour.
+    fn unlock_score(self) -> f32 {
+        match self {
+            BirbColor::Blue => 1000.0,
+            BirbColor::Red => 2500.0,
+            BirbColor::Green => 5000.0,
+            BirbColor::Gold => 10000.0,
+            _ => 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub(crate) enum Pattern {
+    Plain,
+    /// Dark wingtips.
+    Tips,
+    Stripes,
+    Spots,
+}
+
+impl Pattern {
+    const ALL: [Pattern; 4] = [
+        Pattern::Plain,
+        Pattern::Tips,
+        Pattern::Stripes,
+        Pattern::Spots,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Pattern::Plain => "Plain",
+            Pattern::Tips => "Tips",
+            Pattern::Stripes => "Stripes",
+            Pattern::Spots => "Spots",
+        }
+    }
+
+    fn unlock_score(self) -> f32 {
+        match self {
+            Pattern::Stripes => 3000.0,
+            Pattern::Spots => 8000.0,
+            _ => 0.0,
+        }
+    }
+}
+
+/// How the birb is painted, and the best score ever reached to unlock more options.
+#[derive(Resource, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct Customization {
+    pub(crate) body: BirbColor,
+    pub(crate) wings: BirbColor,
+    pub(crate) belly: BirbColor,
+    pub(crate) pattern: Pattern,
+    pub(crate) best_score: f32,
+}
+
+impl Default for Customization {
+    fn default() -> Self {
+        Self {
+            body: BirbColor::Grey,
+            wings: BirbColor::Grey,
+            belly: BirbColor::White,
+            pattern: Pattern::Plain,
+            best_score: 0.0,
+        }
+    }
+}
+
+/// Half the width of the body in model space, everything further out is wing.
+const BODY_HALF_WIDTH: f32 = 1.2;
+/// Distance from the body where the dark wingtips of [`Pattern::Tips`] start.
+const WINGTIP_START: f32 = 5.0;
+
+impl Customization {
+    /// Colour of a vertex of the model at `position`, in model space.
+    fn paint(&self, position: Vec3) -> Color {
+        let on_wing = position.x.abs() > BODY_HALF_WIDTH;
+        let base = if on_wing {
+            self.wings
+        } else if position.y < 0.0 {
+            self.belly
+        } else {
+            self.body
+        }
+        .color();
+        let darken = |factor: f32| base * factor;
+        match self.pattern {
+            Pattern::Plain => base,
+            Pattern::Tips if position.x.abs() > WINGTIP_START => darken(0.3),
+            Pattern::Tips => base,
+            Pattern::Stripes => {
+                let along = if on_wing { position.x } else { position.z };
+                if (along * 2.0).sin() > 0.3 {
+                    darken(0.65)
+                } else {
+                    base
+                }
+            }
+            Pattern::Spots => {
+                if (position.x * 2.1).sin() * (position.z * 2.3).sin() > 0.55 {
+                    (base * 0.4 + Color::WHITE * 0.6).with_a(1.0)
+                } else {
+                    base
+                }
+            }
+        }
+    }
+
+    fn is_unlocked(&self, choice: Choice) -> bool {
+        let needed = match choice {
+            Choice::Body(color) | Choice::Wings(color) | Choice::Belly(color) => {
+                color.unlock_score()
+            }
+            Choice::Pattern(pattern) => pattern.unlock_score(),
+        };
+        self.best_score >= needed
+    }
+
+    fn is_selected(&self, choice: Choice) -> bool {
+        match choice {
+            Choice::Body(color) => self.body == color,
+            Choice::Wings(color) => self.wings == color,
+            Choice::Belly(color) => self.belly == color,
+            Choice::Pattern(pattern) => self.pattern == pattern,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Choice {
+    Body(BirbColor),
+    Wings(BirbColor),
+    Belly(BirbColor),
+    Pattern(Pattern),
+}
+
+#[derive(Component)]
+struct CustomizationPanel;
+
+#[derive(Component)]
+struct CustomizationButton(Choice);
+
+/// A mesh of the birb painted with the [`Customization`], with the mesh it was painted from.
+#[derive(Component)]
+struct CustomizedMesh {
+    original: Handle<Mesh>,
+}
+
+const SWATCH_SIZE: f32 = 28.0;
+const SELECTED_BORDER: Color = Color::rgb(1.0, 0.843, 0.0);
+const LOCKED_ALPHA: f32 = 0.15;
+
+fn setup_customization_panel(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    customization: Res<Customization>,
+) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+        font_size: 16.0,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(20.0),
+                    top: Val::Px(20.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.0),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+                // Above the species screen, which covers everything.
+                z_index: ZIndex::Global(1),
+                ..default()
+            },
+            CustomizationPanel,
+        ))
+        .with_children(|panel| {
+            panel.spawn(TextBundle::from_section(
+                format!("Best score: {:.0}", customization.best_score),
+                text_style.clone(),
+            ));
+            let rows: [(&str, fn(BirbColor) -> Choice); 3] = [
+                ("Body", Choice::Body),
+                ("Wings", Choice::Wings),
+                ("Belly", Choice::Belly),
+            ];
+            for (label, choice) in rows {
+                panel.spawn(TextBundle::from_section(label, text_style.clone()));
+                panel
+                    .spawn(NodeBundle {
+                        style: Style {
+                            column_gap: Val::Px(4.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        for color in BirbColor::ALL {
+                            spawn_swatch(row, choice(color), color.color(), |_| {});
+                        }
+                    });
+            }
+            panel.spawn(TextBundle::from_section("Pattern", text_style.clone()));
+            panel
+                .spawn(NodeBundle {
+                    style: Style {
+                        column_gap: Val::Px(4.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|row| {
+                    for pattern in Pattern::ALL {
+                        spawn_swatch(row, Choice::Pattern(pattern), Color::DARK_GRAY, |b| {
+                            b.spawn(TextBundle::from_section(
+                                pattern.label(),
+                                text_style.clone(),
+                            ));
+                        });
+                    }
+                });
+        });
+}
+
+fn spawn_swatch(
+    row: &mut ChildBuilder,
+    choice: Choice,
+    color: Color,
+    children: impl FnOnce(&mut ChildBuilder),
+) {
+    row.spawn((
+        ButtonBundle {
+            style: Style {
+                min_width: Val::Px(SWATCH_SIZE),
+                height: Val::Px(SWATCH_SIZE),
+                padding: UiRect::horizontal(Val::Px(4.0)),
+                border: UiRect::all(Val::Px(2.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: color.into(),
+            ..default()
+        },
+        CustomizationButton(choice),
+    ))
+    .with_children(children);
+}
+
+fn despawn_customization_panel(
+    mut commands: Commands,
+    panels: Query<Entity, With<CustomizationPanel>>,
+) {
+    for panel in &panels {
+        commands.entity(panel).despawn_recursive();
+    }
+}
+
+fn customization_buttons(
+    buttons: Query<(&Interaction, &CustomizationButton), Changed<Interaction>>,
+    mut customization: ResMut<Customization>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Pressed || !customization.is_unlocked(button.0) {
+            continue;
+        }
+        match button.0 {
+            Choice::Body(color) => customization.body = color,
+            Choice::Wings(color) => customization.wings = color,
+            Choice::Belly(color) => customization.belly = color,
+            Choice::Pattern(pattern) => customization.pattern = pattern,
+        }
+        config::save(CONFIG_FILE, &*customization);
+    }
+}
+
+fn update_customization_buttons(
+    customization: Res<Customization>,
+    mut buttons: Query<(
+        Ref<CustomizationButton>,
+        &mut BorderColor,
+        &mut BackgroundColor,
+    )>,
+) {
+    for (button, mut border, mut background) in &mut buttons {
+        if !customization.is_changed() && !button.is_added() {
+            continue;
+        }
+        border.0 = if customization.is_selected(button.0) {
+            SELECTED_BORDER
+        } else {
+            Color::NONE
+        };
+        let alpha = if customization.is_unlocked(button.0) {
+            1.0
+        } else {
+            LOCKED_ALPHA
+        };
+        background.0.set_a(alpha);
+    }
+}
+
+/// Paints the meshes of the birb's model and overrides their materials to show the paint.
+fn apply_customization(
+    mut commands: Commands,
+    customization: Res<Customization>,
+    birbs: Query<Entity, With<Birb>>,
+    children: Query<&Children>,
+    mut parts: Query<(
+        &mut Handle<Mesh>,
+        &mut Handle<StandardMaterial>,
+        Option<&CustomizedMesh>,
+    )>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for birb in &birbs {
+        for entity in children.iter_descendants(birb) {
+            let Ok((mut mesh, mut material, customized)) = parts.get_mut(entity) else {
+                continue;
+            };
+            if customized.is_some() && !customization.is_changed() {
+                continue;
+            }
+            let original = customized.map_or_else(|| mesh.clone(), |c| c.original.clone());
+            let Some(mut painted) = meshes.get(&original).cloned() else {
+                continue;
+            };
+            let Some(positions) = painted
+                .attribute(Mesh::ATTRIBUTE_POSITION)
+                .and_then(|p| p.as_float3())
+            else {
+                continue;
+            };
+            let colors: Vec<[f32; 4]> = positions
+                .iter()
+                .map(|p| customization.paint(Vec3::from(*p)).as_linear_rgba_f32())
+                .collect();
+            painted.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+            *mesh = meshes.add(painted);
+
+            if customized.is_none() {
+                // Keep everything about the baked material but its colour.
+                let mut overridden = materials.get(&*material).cloned().unwrap_or_default();
+                overridden.base_color = Color::WHITE;
+                *material = materials.add(overridden);
+                commands.entity(entity).insert(CustomizedMesh { original });
+            }
+        }
+    }
+}
+
+/// Remembers the best score across runs and saves it whenever it unlocks something new.
+fn unlock_by_score(score: Res<ScoreState>, mut customization: ResMut<Customization>) {
+    let best = customization.best_score;
+    if score.hi_score <= best {
+        return;
+    }
+    let unlocks = BirbColor::ALL
+        .iter()
+        .map(|c| c.unlock_score())
+        .chain(Pattern::ALL.iter().map(|p| p.unlock_score()));
+    let unlocked_something = unlocks
+        .filter(|needed| best < *needed && score.hi_score >= *needed)
+        .count()
+        > 0;
+    // Only the paint depends on the customization, so changing the score doesn't repaint.
+    customization.bypass_change_detection().best_score = score.hi_score;
+    if unlocked_something {
+        info!(
+            "New birb colours or patterns unlocked at {:.0}",
+            score.hi_score
+        );
+        config::save(CONFIG_FILE, &*customization);
+    }
+}
@@ -0,0 +1,254 @@
+use std::f32::consts::{PI, TAU};
+
+use bevy::{
+    pbr::{CascadeShadowConfigBuilder, NotShadowCaster},
+    prelude::*,
+};
+
+use crate::{plugins::weather::WeatherState, Collectible, TerrainState};
+
+pub(crate) struct DayNightPlugin;
+
+impl Plugin for DayNightPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TimeOfDay {
+            hour: START_HOUR,
+            day_length: DAY_LENGTH_SECS,
+        })
+        .add_systems(Startup, day_night_setup)
+        .add_systems(
+            Update,
+            (advance_time_of_day, update_sun_and_sky, glow_collectibles).chain(),
+        );
+    }
+}
+
+/// In-game clock driving the sun, sky and ambient light.
+#[derive(Resource)]
+pub(crate) struct TimeOfDay {
+    /// Hour of the day in `0.0..24.0`.
+    pub(crate) hour: f32,
+    /// Real seconds a full day takes.
+    pub(crate) day_length: f32,
+}
+
+impl TimeOfDay {
+    /// Angle of the sun above the horizon in radians, negative at night.
+    pub(crate) fn sun_elevation(&self) -> f32 {
+        ((self.hour - 6.0) / 12.0 * PI).sin() * MAX_SUN_ELEVATION
+    }
+
+    /// 1 at noon, fading through dusk to 0 at night.
+    pub(crate) fn daylight(&self) -> f32 {
+        smoothstep(-0.1, 0.3, self.sun_elevation())
+    }
+}
+
+/// The directional light that gets moved around by the [`TimeOfDay`].
+#[derive(Component)]
+pub(crate) struct Sun;
+
+#[derive(Component)]
+struct SkyDome;
+
+#[derive(Resource)]
+struct SkyDomeMesh(Handle<Mesh>);
+
+const START_HOUR: f32 = 10.0;
+const DAY_LENGTH_SECS: f32 = 600.0;
+const MAX_SUN_ELEVATION: f32 = 0.4 * PI;
+const SUN_ILLUMINANCE: f32 = 100_000.0;
+const AMBIENT_BRIGHTNESS: f32 = 1.0;
+/// Sky dome size as a fraction of the terrain view radius. The dome has to stay inside the
+/// camera's far plane but behind the point where the fog turns opaque.
+const SKY_RADIUS_FRACTION: f32 = 0.95;
+
+const DAY_ZENITH: Color = Color::rgb(0.25, 0.5, 0.9);
+const DAY_HORIZON: Color = Color::rgb(0.7, 0.8, 0.95);
+const DUSK_HORIZON: Color = Color::rgb(0.95, 0.55, 0.3);
+const NIGHT_ZENITH: Color = Color::rgb(0.01, 0.01, 0.04);
+const NIGHT_HORIZON: Color = Color::rgb(0.03, 0.04, 0.1);
+const NOON_SUN: Color = Color::rgb(1.0, 0.98, 0.95);
+const LOW_SUN: Color = Color::rgb(1.0, 0.6, 0.35);
+const NIGHT_AMBIENT: Color = Color::rgb(0.4, 0.45, 0.8);
+/// How much a sky colour channel has to change before the dome gets new colours, about one
+/// step of an 8 bit channel.
+const SKY_COLOR_STEP: f32 = 1.0 / 255.0;
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn mix(a: Color, b: Color, t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    Color::rgb(
+        a.r() + (b.r() - a.r()) * t,
+        a.g() + (b.g() - a.g()) * t,
+        a.b() + (b.b() - a.b()) * t,
+    )
+}
+
+/// Largest difference between the channels of `a` and `b`.
+fn color_distance(a: Color, b: Color) -> f32 {
+    (a.r() - b.r())
+        .abs()
+        .max((a.g() - b.g()).abs())
+        .max((a.b() - b.b()).abs())
+}
+
+fn day_night_setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    terrain_state: Res<TerrainState>,
+) {
+    commands.spawn((
+        DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                shadows_enabled: true,
+                ..default()
+            },
+            cascade_shadow_config: CascadeShadowConfigBuilder {
+                first_cascade_far_bound: 30.0,
+                maximum_distance: 400.0,
+                ..default()
+            }
+            .into(),
+            ..default()
+        },
+        Sun,
+    ));
+
+    let mesh = meshes.add(Mesh::from(shape::UVSphere {
+        radius: 1.0,
+        sectors: 32,
+        stacks: 16,
+    }));
+    commands.insert_resource(SkyDomeMesh(mesh.clone()));
+    commands.spawn((
+        PbrBundle {
+            mesh,
+            material: materials.add(StandardMaterial {
+                base_color: Color::WHITE,
+                unlit: true,
+                fog_enabled: false,
+                cull_mode: None,
+                ..default()
+            }),
+            transform: Transform::from_scale(Vec3::splat(
+                terrain_state.view_radius * SKY_RADIUS_FRACTION,
+            )),
+            ..default()
+        },
+        NotShadowCaster,
+        SkyDome,
+    ));
+}
+
+fn advance_time_of_day(time: Res<Time>, mut time_of_day: ResMut<TimeOfDay>) {
+    let hours = time.delta_seconds() * 24.0 / time_of_day.day_length;
+    time_of_day.hour = (time_of_day.hour + hours) % 24.0;
+}
+
+fn update_sun_and_sky(
+    time_of_day: Res<TimeOfDay>,
+    weather: Res<WeatherState>,
+    sky_mesh: Res<SkyDomeMesh>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut suns: Query<(&mut DirectionalLight, &mut Transform), With<Sun>>,
+    mut sky: Query<&mut Transform, (With<SkyDome>, Without<Sun>, Without<Camera3d>)>,
+    mut cameras: Query<(&GlobalTransform, Option<&mut FogSettings>), With<Camera3d>>,
+    mut ambient: ResMut<AmbientLight>,
+    mut clear_color: ResMut<ClearColor>,
+    mut sky_colors: Local<Option<(Color, Color)>>,
+) {
+    let elevation = time_of_day.sun_elevation();
+    let daylight = time_of_day.daylight();
+    // Low sun turns the light and horizon orange.
+    let dusk = 1.0 - smoothstep(0.0, 0.35, elevation.abs());
+    let overcast = 1.0 - weather.effects.light;
+
+    let azimuth = time_of_day.hour / 24.0 * TAU;
+    for (mut sun, mut transform) in &mut suns {
+        transform.rotation = Quat::from_rotation_y(azimuth) * Quat::from_rotation_x(-elevation);
+        sun.color = mix(NOON_SUN, LOW_SUN, dusk);
+        sun.illuminance = SUN_ILLUMINANCE * daylight * weather.effects.light;
+    }
+
+    let weather_sky = mix(Color::BLACK, weather.effects.fog_color, daylight);
+    let horizon = mix(
+        mix(
+            NIGHT_HORIZON,
+            mix(DAY_HORIZON, DUSK_HORIZON, dusk),
+            daylight,
+        ),
+        weather_sky,
+        overcast,
+    );
+    let zenith = mix(
+        mix(NIGHT_ZENITH, DAY_ZENITH, daylight),
+        weather_sky,
+        overcast,
+    );
+
+    ambient.color = mix(NIGHT_AMBIENT, Color::WHITE, daylight);
+    ambient.brightness = AMBIENT_BRIGHTNESS * (0.05 + 0.95 * daylight) * weather.effects.light;
+    clear_color.0 = horizon;
+
+    for (camera, fog) in &mut cameras {
+        if let Some(mut fog) = fog {
+            fog.color = horizon;
+        }
+        for mut dome in &mut sky {
+            dome.translation = camera.translation();
+        }
+    }
+
+    // Writing the colours re-uploads the whole dome, so only do it once they visibly changed.
+    let visible_change = sky_colors.is_none_or(|(last_horizon, last_zenith)| {
+        color_distance(last_horizon, horizon).max(color_distance(last_zenith, zenith))
+            > SKY_COLOR_STEP
+    });
+    if !visible_change {
+        return;
+    }
+    if let Some(mesh) = meshes.get_mut(&sky_mesh.0) {
+        let Some(positions) = mesh
+            .attribute(Mesh::ATTRIBUTE_POSITION)
+            .and_then(|p| p.as_float3())
+        else {
+            return;
+        };
+        let colors: Vec<[f32; 4]> = positions
+            .iter()
+            .map(|p| {
+                let height = p[1].max(0.0).sqrt();
+                mix(horizon, zenith, height).as_linear_rgba_f32()
+            })
+            .collect();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        *sky_colors = Some((horizon, zenith));
+    }
+}
+
+/// Collectibles light up in their own colour as it gets dark so they can still be found at
+/// night.
+fn glow_collectibles(
+    time_of_day: Res<TimeOfDay>,
+    collectibles: Query<(&Collectible, &Handle<StandardMaterial>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let night = 1.0 - time_of_day.daylight();
+    for (Collectible(kind), handle) in &collectibles {
+        let emissive = kind.color() * night * 2.0;
+        let outdated = materials
+            .get(handle)
+            .is_some_and(|material| material.emissive != emissive);
+        if outdated {
+            if let Some(material) = materials.get_mut(handle) {
+                material.emissive = emissive;
+            }
+        }
+    }
+}
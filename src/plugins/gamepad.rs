@@ -0,0 +1,174 @@
+use bevy::prelude::*;
+
+use crate::plugins::controls::{ControlsSet, InputAction, PendingActions};
+
+/// Analog wing control: each trigger sets how far one wing extends and the stick on the same
+/// side picks which of its joints do the work.
+///
+/// Gamepad buttons (poop, pause, pitch) go through the regular
+/// [`InputBindings`](crate::plugins::controls::InputBindings).
+pub(crate) struct GamepadPlugin;
+
+impl Plugin for GamepadPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, gamepad_wings.in_set(ControlsSet::Gather));
+    }
+}
+
+const STICK_DEADZONE: f32 = 0.15;
+
+/// How much of a trigger's effort goes to `joint` (0 = next to the body, 3 = wing tip).
+///
+/// A centred stick spreads the effort evenly. Pushing it up moves the effort towards the tip,
+/// pulling it down towards the body.
+fn joint_weight(joint: u8, stick_y: f32) -> f32 {
+    let deflection = if stick_y.abs() < STICK_DEADZONE {
+        0.0
+    } else {
+        stick_y.abs()
+    };
+    let focus = (stick_y + 1.0) * 1.5;
+    let peak = (1.0 - (joint as f32 - focus).abs()).max(0.0);
+    (1.0 - deflection) + deflection * peak
+}
+
+fn gamepad_wings(
+    gamepads: Res<Gamepads>,
+    buttons: Res<Axis<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut pending: ResMut<PendingActions>,
+) {
+    for gamepad in gamepads.iter() {
+        for (trigger, stick, wing_joint) in [
+            (
+                GamepadButtonType::LeftTrigger2,
+                GamepadAxisType::LeftStickY,
+                InputAction::LeftWingJoint as fn(u8) -> InputAction,
+            ),
+            (
+                GamepadButtonType::RightTrigger2,
+                GamepadAxisType::RightStickY,
+                InputAction::RightWingJoint,
+            ),
+        ] {
+            let depth = buttons
+                .get(GamepadButton::new(gamepad, trigger))
+                .unwrap_or(0.0);
+            let stick_y = axes.get(GamepadAxis::new(gamepad, stick)).unwrap_or(0.0);
+            for joint in 0..4 {
+                pending.set_analog(wing_joint(joint), depth * joint_weight(joint, stick_y));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::{
+        gamepad::{GamepadConnection, GamepadConnectionEvent, GamepadInfo},
+        InputPlugin,
+    };
+
+    use super::*;
+    use crate::plugins::controls::{commit_actions, read_bindings, InputBindings, Rebinding};
+
+    #[test]
+    fn centred_stick_spreads_effort_evenly() {
+        for joint in 0..4 {
+            assert_eq!(joint_weight(joint, 0.0), 1.0);
+        }
+    }
+
+    #[test]
+    fn deadzone_is_ignored() {
+        for joint in 0..4 {
+            assert_eq!(joint_weight(joint, STICK_DEADZONE * 0.5), 1.0);
+            assert_eq!(joint_weight(joint, -STICK_DEADZONE * 0.5), 1.0);
+        }
+    }
+
+    #[test]
+    fn full_up_moves_effort_to_the_tip() {
+        assert_eq!(joint_weight(3, 1.0), 1.0);
+        for joint in 0..3 {
+            assert_eq!(joint_weight(joint, 1.0), 0.0);
+        }
+    }
+
+    #[test]
+    fn full_down_moves_effort_to_the_body() {
+        assert_eq!(joint_weight(0, -1.0), 1.0);
+        for joint in 1..4 {
+            assert_eq!(joint_weight(joint, -1.0), 0.0);
+        }
+    }
+
+    /// An app gathering the default bindings and the gamepad wings, with one gamepad connected.
+    fn gamepad_app() -> (App, Gamepad) {
+        let mut app = App::new();
+        app.add_plugins(InputPlugin)
+            .init_resource::<InputBindings>()
+            .init_resource::<PendingActions>()
+            .init_resource::<Rebinding>()
+            .init_resource::<Input<InputAction>>()
+            .init_resource::<Axis<InputAction>>()
+            .add_systems(
+                Update,
+                ((read_bindings, gamepad_wings), commit_actions).chain(),
+            );
+
+        let gamepad = Gamepad::new(0);
+        app.world.send_event(GamepadConnectionEvent::new(
+            gamepad,
+            GamepadConnection::Connected(GamepadInfo {
+                name: "Test pad".to_string(),
+            }),
+        ));
+        app.update();
+        (app, gamepad)
+    }
+
+    #[test]
+    fn triggers_and_sticks_drive_wing_joints() {
+        let (mut app, gamepad) = gamepad_app();
+        app.world.resource_mut::<Axis<GamepadButton>>().set(
+            GamepadButton::new(gamepad, GamepadButtonType::RightTrigger2),
+            0.8,
+        );
+        app.world
+            .resource_mut::<Axis<GamepadAxis>>()
+            .set(GamepadAxis::new(gamepad, GamepadAxisType::RightStickY), 1.0);
+        app.update();
+
+        let axes = app.world.resource::<Axis<InputAction>>();
+        assert_eq!(axes.get(InputAction::RightWingJoint(3)), Some(0.8));
+        for joint in 0..3 {
+            assert_eq!(axes.get(InputAction::RightWingJoint(joint)), Some(0.0));
+        }
+        // The untouched left trigger leaves the other wing alone.
+        for joint in 0..4 {
+            assert_eq!(axes.get(InputAction::LeftWingJoint(joint)), Some(0.0));
+        }
+    }
+
+    #[test]
+    fn face_buttons_poop_and_pause() {
+        let (mut app, gamepad) = gamepad_app();
+        for (button, action) in [
+            (GamepadButtonType::South, InputAction::Poop),
+            (GamepadButtonType::East, InputAction::Pause),
+        ] {
+            let button = GamepadButton::new(gamepad, button);
+            app.world
+                .resource_mut::<Input<GamepadButton>>()
+                .press(button);
+            app.update();
+            assert!(app.world.resource::<Input<InputAction>>().pressed(action));
+            app.world
+                .resource_mut::<Input<GamepadButton>>()
+                .release(button);
+            app.update();
+            assert!(!app.world.resource::<Input<InputAction>>().pressed(action));
+        }
+    }
+}
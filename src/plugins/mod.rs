@@ -1,3 +1,27 @@
+pub mod animation;
+pub mod assist;
 pub mod camera;
+pub mod cloth;
+pub mod control_mode;
+pub mod controls;
+pub mod courses;
+pub mod customization;
+pub mod day_night;
+pub mod gamepad;
 pub mod poop;
+pub mod power_ups;
+pub mod replay;
+pub mod scan_codes;
 pub mod score;
+pub mod secondary;
+pub mod skeleton;
+pub mod species;
+pub mod time_trial;
+pub mod touch;
+pub mod trails;
+pub mod tuning;
+pub mod typing;
+pub mod weather;
+pub mod wind;
+pub mod wind_streaks;
+pub mod wing_hud;
@@ -1,4 +1,7 @@
-use crate::{Birb, Layer};
+use crate::{
+    plugins::{controls::InputAction, power_ups::PowerUps, species::SelectedSpecies},
+    Birb, Layer,
+};
 use bevy::prelude::*;
 use bevy_xpbd_3d::prelude::*;
 
@@ -44,15 +47,19 @@ pub struct Poop;
 
 pub(crate) fn poop(
     mut commands: Commands,
-    input: Res<Input<KeyCode>>,
+    actions: Res<Input<InputAction>>,
     birb: Query<(&Transform, &LinearVelocity, &AngularVelocity), With<Birb>>,
     mut poop_state: ResMut<PoopState>,
+    species: Res<SelectedSpecies>,
+    mut power_ups: ResMut<PowerUps>,
     time: Res<Time>,
 ) {
     // cooldown
-    if time.elapsed_seconds_f64() - poop_state.last_poop > 0.5 {
+    if power_ups.poops > 0
+        && time.elapsed_seconds_f64() - poop_state.last_poop > species.0.stats().poop_cooldown
+    {
         for (bt, lv, av) in &birb {
-            if input.pressed(KeyCode::Space) {
+            if actions.pressed(InputAction::Poop) {
                 commands
                     .spawn(PbrBundle {
                         mesh: poop_state.poop_mesh.clone(),
@@ -73,6 +80,7 @@ pub(crate) fn poop(
                     ))
                     .insert(Poop);
                 poop_state.last_poop = time.elapsed_seconds_f64();
+                power_ups.poops -= 1;
             }
         }
     }
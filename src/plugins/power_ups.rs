@@ -0,0 +1,313 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+use rand::Rng;
+
+use crate::{AppState, Birb, BirbState, Collectible};
+
+/// Collectible kinds and the timed buffs they give the birb, plus the stamina and poop supply
+/// some of them refill.
+pub(crate) struct PowerUpsPlugin;
+
+impl Plugin for PowerUpsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Collected>()
+            .init_resource::<PowerUps>()
+            .add_systems(Startup, setup_power_up_hud)
+            .add_systems(
+                Update,
+                (
+                    (collect_power_ups, tick_power_ups, use_stamina)
+                        .chain()
+                        .run_if(in_state(AppState::InGame)),
+                    (apply_speed_boost, apply_magnet, apply_slow_motion)
+                        .after(tick_power_ups)
+                        .run_if(in_state(AppState::InGame)),
+                    update_power_up_hud,
+                ),
+            );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub(crate) enum CollectibleKind {
+    ScoreOrb,
+    SpeedBoost,
+    StaminaRefill,
+    Magnet,
+    PoopRefill,
+    SlowMotion,
+}
+
+impl CollectibleKind {
+    pub(crate) const ALL: [CollectibleKind; 6] = [
+        CollectibleKind::ScoreOrb,
+        CollectibleKind::SpeedBoost,
+        CollectibleKind::StaminaRefill,
+        CollectibleKind::Magnet,
+        CollectibleKind::PoopRefill,
+        CollectibleKind::SlowMotion,
+    ];
+
+    /// Picks a kind, with score orbs as common as all the power-ups together.
+    pub(crate) fn random(rng: &mut impl Rng) -> Self {
+        if rng.gen_bool(0.5) {
+            CollectibleKind::ScoreOrb
+        } else {
+            CollectibleKind::ALL[rng.gen_range(1..CollectibleKind::ALL.len())]
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            CollectibleKind::ScoreOrb => "Score",
+            CollectibleKind::SpeedBoost => "Speed boost",
+            CollectibleKind::StaminaRefill => "Stamina",
+            CollectibleKind::Magnet => "Magnet",
+            CollectibleKind::PoopRefill => "Poop refill",
+            CollectibleKind::SlowMotion => "Slow motion",
+        }
+    }
+
+    pub(crate) fn color(self) -> Color {
+        match self {
+            CollectibleKind::ScoreOrb => Color::rgb(1.0, 0.843, 0.0),
+            CollectibleKind::SpeedBoost => Color::rgb(1.0, 0.35, 0.1),
+            CollectibleKind::StaminaRefill => Color::rgb(0.3, 0.9, 0.3),
+            CollectibleKind::Magnet => Color::rgb(0.6, 0.3, 0.9),
+            CollectibleKind::PoopRefill => Color::rgb_u8(140, 69, 18),
+            CollectibleKind::SlowMotion => Color::rgb(0.5, 0.8, 1.0),
+        }
+    }
+
+    /// Shape of a collectible of this kind, about as big as a sphere of `radius`.
+    pub(crate) fn mesh(self, radius: f32) -> Mesh {
+        match self {
+            CollectibleKind::ScoreOrb => Mesh::from(shape::UVSphere {
+                radius,
+                sectors: 14,
+                stacks: 14,
+            }),
+            CollectibleKind::SpeedBoost => Mesh::from(shape::Capsule {
+                radius: radius * 0.5,
+                depth: radius,
+                ..default()
+            }),
+            CollectibleKind::StaminaRefill => Mesh::from(shape::Cube { size: radius * 1.4 }),
+            CollectibleKind::Magnet => Mesh::from(shape::Torus {
+                radius: radius * 0.8,
+                ring_radius: radius * 0.3,
+                ..default()
+            }),
+            // Lumpy, like what it refills.
+            CollectibleKind::PoopRefill => Mesh::from(shape::UVSphere {
+                radius: radius * 0.9,
+                sectors: 6,
+                stacks: 4,
+            }),
+            CollectibleKind::SlowMotion => Mesh::from(shape::Cylinder {
+                radius: radius * 0.8,
+                height: radius * 0.6,
+                ..default()
+            }),
+        }
+    }
+
+    /// How long the buff lasts, in seconds. Zero for instant ones.
+    fn duration(self) -> f32 {
+        match self {
+            CollectibleKind::SpeedBoost => 5.0,
+            CollectibleKind::Magnet => 8.0,
+            CollectibleKind::SlowMotion => 4.0,
+            CollectibleKind::ScoreOrb
+            | CollectibleKind::StaminaRefill
+            | CollectibleKind::PoopRefill => 0.0,
+        }
+    }
+}
+
+/// Sent when the birb or its poop picks up a collectible.
+#[derive(Event)]
+pub(crate) struct Collected(pub(crate) CollectibleKind);
+
+const MAX_STAMINA: f32 = 100.0;
+/// Stamina used per second per unit of wing joint angular velocity on the down-stroke.
+const STAMINA_DRAIN: f32 = 0.4;
+/// Stamina regained per second.
+const STAMINA_REGEN: f32 = 6.0;
+/// How strong flaps are with no stamina left.
+const EXHAUSTED_FLAP: f32 = 0.3;
+pub(crate) const MAX_POOPS: u32 = 10;
+/// Acceleration along the birb's forward direction during a speed boost.
+const BOOST_ACCELERATION: f32 = 12.0;
+const MAGNET_RADIUS: f32 = 80.0;
+/// World units per second collectibles move towards the birb.
+const MAGNET_SPEED: f32 = 40.0;
+const SLOW_MOTION_SPEED: f64 = 0.5;
+
+struct ActiveEffect {
+    kind: CollectibleKind,
+    remaining: f32,
+}
+
+/// Buffs active on the birb and the supplies power-ups refill.
+#[derive(Resource)]
+pub(crate) struct PowerUps {
+    active: Vec<ActiveEffect>,
+    pub(crate) stamina: f32,
+    pub(crate) poops: u32,
+}
+
+impl Default for PowerUps {
+    fn default() -> Self {
+        Self {
+            active: Vec::new(),
+            stamina: MAX_STAMINA,
+            poops: MAX_POOPS,
+        }
+    }
+}
+
+impl PowerUps {
+    pub(crate) fn is_active(&self, kind: CollectibleKind) -> bool {
+        self.active.iter().any(|effect| effect.kind == kind)
+    }
+
+    /// Multiplier on the flap force, lower once the birb is out of stamina.
+    pub(crate) fn flap_strength(&self) -> f32 {
+        EXHAUSTED_FLAP + (1.0 - EXHAUSTED_FLAP) * (self.stamina / MAX_STAMINA).min(1.0).sqrt()
+    }
+
+    /// Drops all buffs and refills everything, e.g. after a respawn.
+    pub(crate) fn reset(&mut self) {
+        *self = default();
+    }
+}
+
+fn collect_power_ups(mut collected: EventReader<Collected>, mut power_ups: ResMut<PowerUps>) {
+    for Collected(kind) in collected.read() {
+        match kind {
+            CollectibleKind::ScoreOrb => {}
+            CollectibleKind::StaminaRefill => power_ups.stamina = MAX_STAMINA,
+            CollectibleKind::PoopRefill => power_ups.poops = MAX_POOPS,
+            CollectibleKind::SpeedBoost | CollectibleKind::Magnet | CollectibleKind::SlowMotion => {
+                // Picking up the same kind again restarts its timer.
+                power_ups.active.retain(|effect| effect.kind != *kind);
+                power_ups.active.push(ActiveEffect {
+                    kind: *kind,
+                    remaining: kind.duration(),
+                });
+            }
+        }
+    }
+}
+
+/// Counts down in real time, as slow motion also slows down the virtual clock.
+fn tick_power_ups(time: Res<Time<Real>>, mut power_ups: ResMut<PowerUps>) {
+    if power_ups.active.is_empty() {
+        return;
+    }
+    for effect in &mut power_ups.active {
+        effect.remaining -= time.delta_seconds();
+    }
+    power_ups.active.retain(|effect| effect.remaining > 0.0);
+}
+
+fn use_stamina(time: Res<Time>, birb_state: Res<BirbState>, mut power_ups: ResMut<PowerUps>) {
+    let effort: f32 = birb_state.angular_velocity.iter().map(|v| v.max(0.0)).sum();
+    let change = (STAMINA_REGEN - effort * STAMINA_DRAIN) * time.delta_seconds();
+    power_ups.stamina = (power_ups.stamina + change).clamp(0.0, MAX_STAMINA);
+}
+
+fn apply_speed_boost(
+    time: Res<Time>,
+    power_ups: Res<PowerUps>,
+    mut birbs: Query<(&Transform, &mut LinearVelocity), With<Birb>>,
+) {
+    if !power_ups.is_active(CollectibleKind::SpeedBoost) {
+        return;
+    }
+    for (transform, mut velocity) in &mut birbs {
+        // The birb flies towards its local +Z.
+        velocity.0 += transform.rotation * Vec3::Z * BOOST_ACCELERATION * time.delta_seconds();
+    }
+}
+
+fn apply_magnet(
+    time: Res<Time>,
+    power_ups: Res<PowerUps>,
+    birbs: Query<&Transform, With<Birb>>,
+    mut collectibles: Query<&mut Transform, (With<Collectible>, Without<Birb>)>,
+) {
+    if !power_ups.is_active(CollectibleKind::Magnet) {
+        return;
+    }
+    let Ok(birb) = birbs.get_single() else {
+        return;
+    };
+    for mut transform in &mut collectibles {
+        let to_birb = birb.translation - transform.translation;
+        if to_birb.length() < MAGNET_RADIUS {
+            transform.translation +=
+                to_birb.normalize_or_zero() * MAGNET_SPEED * time.delta_seconds();
+        }
+    }
+}
+
+/// Slows down the game clock and the physics, which steps with real time, together.
+fn apply_slow_motion(
+    power_ups: Res<PowerUps>,
+    mut time: ResMut<Time<Virtual>>,
+    mut physics_time: ResMut<Time<Physics>>,
+) {
+    let speed = if power_ups.is_active(CollectibleKind::SlowMotion) {
+        SLOW_MOTION_SPEED
+    } else {
+        1.0
+    };
+    if time.relative_speed_f64() != speed {
+        time.set_relative_speed_f64(speed);
+    }
+    if physics_time.relative_speed_f64() != speed {
+        physics_time.set_relative_speed_f64(speed);
+    }
+}
+
+#[derive(Component)]
+struct PowerUpText;
+
+fn setup_power_up_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                font_size: 20.0,
+                color: Color::WHITE,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(10.0),
+            bottom: Val::Px(130.0),
+            ..default()
+        }),
+        PowerUpText,
+    ));
+}
+
+fn update_power_up_hud(power_ups: Res<PowerUps>, mut texts: Query<&mut Text, With<PowerUpText>>) {
+    if !power_ups.is_changed() {
+        return;
+    }
+    let mut value = format!(
+        "Stamina: {:.0}%\nPoops: {}/{MAX_POOPS}\n",
+        power_ups.stamina / MAX_STAMINA * 100.0,
+        power_ups.poops
+    );
+    for effect in &power_ups.active {
+        value += &format!("{} {:.1}s\n", effect.kind.label(), effect.remaining);
+    }
+    for mut text in &mut texts {
+        text.sections[0].value.clone_from(&value);
+    }
+}
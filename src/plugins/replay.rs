@@ -0,0 +1,488 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, time::TimeUpdateStrategy, utils::Instant};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config,
+    plugins::{
+        assist::AssistMode,
+        controls::{ControlsSet, InputAction, PendingActions},
+        courses::SelectedCourse,
+        species::{SelectedSpecies, Species},
+        tuning::{FlightParams, TuningSet},
+        weather::{WeatherMode, WeatherState},
+        wind::WindField,
+    },
+    AppState, Birb, BirbState,
+};
+
+const REPLAY_DIR: &str = "replays";
+
+/// Records every run and plays back runs passed with `--replay <file>`.
+///
+/// The game drives its own clock so that a replay can feed back the exact frame times of the
+/// recording. Once the birb spawns the clock stops until it has loaded and then restarts from
+/// zero, so both start from the same state however long the menus took.
+///
+/// Has to be added before the plugins that use the [`RunSeed`], the `SpeciesPlugin` and the
+/// `CoursesPlugin`.
+pub(crate) struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        let playback = replay_argument().and_then(|path| {
+            let replay = config::load_from(".", &path);
+            if replay.is_none() {
+                error!("Could not load replay {path}");
+            }
+            replay
+        });
+        let seed = playback
+            .as_ref()
+            .map_or_else(rand::random, |r: &Replay| r.seed);
+        if let Some(replay) = &playback {
+            // Skip the species screen and fly the recorded bird and course.
+            app.insert_resource(SelectedSpecies(replay.species));
+            app.insert_resource(replay.course.clone());
+            app.world
+                .resource_mut::<NextState<AppState>>()
+                .set(AppState::InGame);
+        }
+
+        app.insert_resource(RunSeed(seed))
+            .insert_resource(ReplayMode::Waiting(playback.map(Playback::new)))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::ZERO))
+            .init_resource::<Recording>()
+            .add_systems(
+                PreUpdate,
+                (
+                    play_inputs
+                        .in_set(ControlsSet::Override)
+                        .in_set(PlaybackSet),
+                    record_inputs.after(ControlsSet::Derive).after(TuningSet),
+                ),
+            )
+            .add_systems(Update, save_replay)
+            .add_systems(Last, drive_time);
+    }
+}
+
+/// Feeds the inputs of a replay into the [`PendingActions`], replacing everything else.
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+pub(crate) struct PlaybackSet;
+
+/// `--replay <file>` from the command line.
+fn replay_argument() -> Option<String> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--replay");
+    args.next()?;
+    args.next()
+}
+
+/// Seed for everything random that affects the flight, so a replay gets the same world.
+#[derive(Resource, Clone, Copy)]
+pub(crate) struct RunSeed(pub(crate) u64);
+
+/// Independent random streams, so systems drawing numbers in a different order don't
+/// disturb each other.
+#[derive(Clone, Copy)]
+pub(crate) enum RngStream {
+    Collectibles,
+    Wind,
+    Weather,
+    /// Drawn separately from the positions, so older replays keep their collectibles in place.
+    CollectibleKinds,
+}
+
+impl RunSeed {
+    pub(crate) fn rng(self, stream: RngStream) -> StdRng {
+        StdRng::seed_from_u64(self.0.wrapping_add(stream as u64))
+    }
+}
+
+/// Everything needed to fly a run again.
+#[derive(Serialize, Deserialize, Default)]
+struct Replay {
+    seed: u64,
+    #[serde(default)]
+    species: Species,
+    #[serde(default)]
+    course: SelectedCourse,
+    flight_params: FlightParams,
+    /// Changes made on the tuning panel during the run.
+    #[serde(default)]
+    tuning: Vec<TuningChange>,
+    weather: WeatherMode,
+    /// Length of every frame in nanoseconds.
+    #[serde(with = "deltas")]
+    deltas: Vec<u32>,
+    /// State of all [`InputAction`]s, one entry per run of identical frames.
+    inputs: Vec<InputRun>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct TuningChange {
+    /// First frame flown with the new parameters.
+    frame: u32,
+    flight_params: FlightParams,
+}
+
+/// Frame lengths as base64 of their little-endian bytes, which is far more compact than a list of
+/// numbers. Older replays with a plain list still load.
+mod deltas {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Encoded {
+        Bytes(String),
+        List(Vec<u32>),
+    }
+
+    pub(super) fn serialize<S: Serializer>(
+        deltas: &[u32],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let bytes: Vec<u8> = deltas
+            .iter()
+            .flat_map(|delta| delta.to_le_bytes())
+            .collect();
+        serializer.serialize_str(&STANDARD.encode(bytes))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u32>, D::Error> {
+        match Encoded::deserialize(deserializer)? {
+            Encoded::Bytes(text) => {
+                let bytes = STANDARD.decode(text).map_err(D::Error::custom)?;
+                Ok(bytes
+                    .chunks_exact(4)
+                    .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                    .collect())
+            }
+            Encoded::List(deltas) => Ok(deltas),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+struct InputRun {
+    frames: u32,
+    /// Bit `n` is set while `InputAction::ALL[n]` is pressed.
+    pressed: u32,
+    /// Analog value of every wing joint, in `BirbState::angles` order.
+    analog: [f32; 8],
+}
+
+impl Replay {
+    fn push_input(&mut self, pressed: u32, analog: [f32; 8]) {
+        match self.inputs.last_mut() {
+            Some(run)
+                if run.pressed == pressed && run.analog == analog && run.frames < u32::MAX =>
+            {
+                run.frames += 1
+            }
+            _ => self.inputs.push(InputRun {
+                frames: 1,
+                pressed,
+                analog,
+            }),
+        }
+    }
+
+    fn frame_count(&self) -> usize {
+        self.inputs.iter().map(|run| run.frames as usize).sum()
+    }
+
+    /// The parameters flown with at the end of the recording so far.
+    fn current_flight_params(&self) -> &FlightParams {
+        self.tuning
+            .last()
+            .map_or(&self.flight_params, |change| &change.flight_params)
+    }
+}
+
+struct Playback {
+    replay: Replay,
+    frame: usize,
+    run: usize,
+    frame_in_run: u32,
+    /// Frame whose inputs are played next.
+    input_frame: u32,
+    /// Index of the next tuning change.
+    tuning: usize,
+}
+
+impl Playback {
+    fn new(mut replay: Replay) -> Self {
+        replay.deltas.truncate(replay.frame_count());
+        Self {
+            replay,
+            frame: 0,
+            run: 0,
+            frame_in_run: 0,
+            input_frame: 0,
+            tuning: 0,
+        }
+    }
+
+    fn next_delta(&mut self) -> Option<Duration> {
+        let delta = self.replay.deltas.get(self.frame)?;
+        self.frame += 1;
+        Some(Duration::from_nanos(*delta as u64))
+    }
+
+    /// Tuning changes that take effect in the frame whose inputs are played next.
+    fn next_tuning(&mut self) -> Option<&FlightParams> {
+        let change = self.replay.tuning.get(self.tuning)?;
+        if change.frame > self.input_frame {
+            return None;
+        }
+        self.tuning += 1;
+        Some(&change.flight_params)
+    }
+
+    fn next_input(&mut self) -> Option<InputRun> {
+        let run = *self.replay.inputs.get(self.run)?;
+        self.input_frame += 1;
+        self.frame_in_run += 1;
+        if self.frame_in_run >= run.frames {
+            self.run += 1;
+            self.frame_in_run = 0;
+        }
+        Some(run)
+    }
+}
+
+#[derive(Resource)]
+enum ReplayMode {
+    /// The birb is still loading, with the replay to play once it is there. Time only runs until
+    /// the birb spawns.
+    Waiting(Option<Playback>),
+    Recording,
+    Playing(Playback),
+    /// A replay has ended and the player has taken over.
+    Free,
+}
+
+#[derive(Resource, Default)]
+struct Recording(Replay);
+
+/// Starts recording or playing once the birb is ready and sets the length of the next frame.
+fn drive_time(
+    birb_state: Res<BirbState>,
+    birbs: Query<(), With<Birb>>,
+    seed: Res<RunSeed>,
+    species: Res<SelectedSpecies>,
+    course: Res<SelectedCourse>,
+    mut mode: ResMut<ReplayMode>,
+    mut recording: ResMut<Recording>,
+    mut flight_params: ResMut<FlightParams>,
+    mut weather: ResMut<WeatherState>,
+    mut wind: ResMut<WindField>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut assist: ResMut<AssistMode>,
+    mut strategy: ResMut<TimeUpdateStrategy>,
+    mut last_frame: Local<Option<Instant>>,
+) {
+    let now = Instant::now();
+    let measured = last_frame
+        .replace(now)
+        .map_or(Duration::ZERO, |last| now - last);
+
+    if birb_state.wing_joints.is_some() {
+        if let ReplayMode::Waiting(playback) = &mut *mode {
+            // Wind, weather and cooldowns follow the clock, which ran for as long as the menus
+            // were open.
+            *virtual_time = Time::from_max_delta(virtual_time.max_delta());
+            *wind = WindField::new(seed.0 as u32, seed.rng(RngStream::Wind));
+            *mode = match playback.take() {
+                Some(playback) => {
+                    info!("Playing replay with seed {}", seed.0);
+                    *flight_params = playback.replay.flight_params.clone();
+                    *weather =
+                        WeatherState::new(playback.replay.weather, seed.rng(RngStream::Weather));
+                    // The recorded wing efforts already contain the assisted flap waves.
+                    *assist = AssistMode::Off;
+                    ReplayMode::Playing(playback)
+                }
+                None => {
+                    recording.0 = Replay {
+                        seed: seed.0,
+                        species: species.0,
+                        course: course.clone(),
+                        flight_params: flight_params.clone(),
+                        weather: weather.mode,
+                        ..default()
+                    };
+                    *weather = WeatherState::new(weather.mode, seed.rng(RngStream::Weather));
+                    ReplayMode::Recording
+                }
+            };
+        }
+    }
+
+    let delta = match &mut *mode {
+        ReplayMode::Waiting(_) if birbs.is_empty() => measured,
+        ReplayMode::Waiting(_) => Duration::ZERO,
+        ReplayMode::Recording => {
+            let nanos = measured.as_nanos().min(u32::MAX as u128) as u32;
+            recording.0.deltas.push(nanos);
+            Duration::from_nanos(nanos as u64)
+        }
+        ReplayMode::Playing(playback) => match playback.next_delta() {
+            Some(delta) => delta,
+            None => {
+                info!("Replay finished");
+                *mode = ReplayMode::Free;
+                measured
+            }
+        },
+        ReplayMode::Free => measured,
+    };
+    *strategy = TimeUpdateStrategy::ManualDuration(delta);
+}
+
+fn play_inputs(
+    mut mode: ResMut<ReplayMode>,
+    mut pending: ResMut<PendingActions>,
+    mut flight_params: ResMut<FlightParams>,
+) {
+    let ReplayMode::Playing(playback) = &mut *mode else {
+        return;
+    };
+    while let Some(params) = playback.next_tuning() {
+        *flight_params = params.clone();
+    }
+    let Some(run) = playback.next_input() else {
+        return;
+    };
+    pending.clear();
+    for (bit, action) in InputAction::ALL.into_iter().enumerate() {
+        if run.pressed & (1 << bit) != 0 {
+            pending.press(action);
+        }
+    }
+    for (i, value) in run.analog.into_iter().enumerate() {
+        pending.set_analog(InputAction::wing_joint(i), value);
+    }
+}
+
+fn record_inputs(
+    mode: Res<ReplayMode>,
+    actions: Res<Input<InputAction>>,
+    axes: Res<Axis<InputAction>>,
+    flight_params: Res<FlightParams>,
+    mut recording: ResMut<Recording>,
+) {
+    if !matches!(*mode, ReplayMode::Recording) {
+        return;
+    }
+    if *flight_params != *recording.0.current_flight_params() {
+        let frame = recording.0.frame_count() as u32;
+        recording.0.tuning.push(TuningChange {
+            frame,
+            flight_params: flight_params.clone(),
+        });
+    }
+    let pressed = InputAction::ALL
+        .into_iter()
+        .enumerate()
+        .filter(|(_, action)| actions.pressed(*action))
+        .fold(0, |bits, (bit, _)| bits | 1 << bit);
+    let analog = std::array::from_fn(|i| axes.get(InputAction::wing_joint(i)).unwrap_or(0.0));
+    recording.0.push_input(pressed, analog);
+}
+
+fn save_replay(actions: Res<Input<InputAction>>, mode: Res<ReplayMode>, recording: Res<Recording>) {
+    if !actions.just_pressed(InputAction::SaveReplay) {
+        return;
+    }
+    if !matches!(*mode, ReplayMode::Recording) {
+        warn!("Only recorded runs can be saved");
+        return;
+    }
+    let recorded = &recording.0;
+    // The length of the upcoming frame is already recorded, its inputs aren't.
+    let frames = recorded.frame_count();
+    let replay = Replay {
+        seed: recorded.seed,
+        species: recorded.species,
+        course: recorded.course.clone(),
+        flight_params: recorded.flight_params.clone(),
+        tuning: recorded.tuning.clone(),
+        weather: recorded.weather,
+        deltas: recorded.deltas[..frames].to_vec(),
+        inputs: recorded.inputs.clone(),
+    };
+    // Saving again later in the same run overwrites the shorter replay.
+    config::save_to(REPLAY_DIR, &format!("replay-{}.ron", replay.seed), &replay);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deltas_survive_a_round_trip() {
+        let replay = Replay {
+            deltas: vec![16_666_667, 0, 8_333_333, u32::MAX],
+            ..default()
+        };
+        let text = ron::ser::to_string_pretty(&replay, default()).unwrap();
+        let loaded: Replay = ron::from_str(&text).unwrap();
+        assert_eq!(loaded.deltas, replay.deltas);
+    }
+
+    #[test]
+    fn deltas_load_from_a_plain_list() {
+        let text = ron::ser::to_string(&Replay::default())
+            .unwrap()
+            .replace("deltas:\"\"", "deltas:[1,2,3]");
+        let loaded: Replay = ron::from_str(&text).unwrap();
+        assert_eq!(loaded.deltas, [1, 2, 3]);
+    }
+
+    #[test]
+    fn identical_inputs_share_a_run() {
+        let mut replay = Replay::default();
+        replay.push_input(0b1, [0.0; 8]);
+        replay.push_input(0b1, [0.0; 8]);
+        replay.push_input(0b10, [0.0; 8]);
+        replay.push_input(0b10, [0.5; 8]);
+        let runs: Vec<_> = replay
+            .inputs
+            .iter()
+            .map(|run| (run.frames, run.pressed))
+            .collect();
+        assert_eq!(runs, [(2, 0b1), (1, 0b10), (1, 0b10)]);
+        assert_eq!(replay.frame_count(), 4);
+    }
+
+    #[test]
+    fn full_runs_are_split() {
+        let mut replay = Replay::default();
+        replay.push_input(0, [0.0; 8]);
+        replay.inputs[0].frames = u32::MAX;
+        replay.push_input(0, [0.0; 8]);
+        let runs: Vec<_> = replay.inputs.iter().map(|run| run.frames).collect();
+        assert_eq!(runs, [u32::MAX, 1]);
+    }
+
+    #[test]
+    fn playback_follows_the_runs() {
+        let mut replay = Replay::default();
+        for pressed in [1, 1, 2, 3, 3] {
+            replay.push_input(pressed, [0.0; 8]);
+            replay.deltas.push(pressed);
+        }
+        let mut playback = Playback::new(replay);
+        let played: Vec<_> = std::iter::from_fn(|| playback.next_input())
+            .map(|run| run.pressed)
+            .collect();
+        assert_eq!(played, [1, 1, 2, 3, 3]);
+    }
+}
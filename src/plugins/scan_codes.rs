@@ -0,0 +1,111 @@
+//! Physical key positions, so the home-row controls work on any keyboard layout.
+
+use bevy::{input::keyboard::KeyboardInput, prelude::*, utils::HashMap};
+
+use crate::plugins::controls::ControlsSet;
+
+pub(crate) struct ScanCodesPlugin;
+
+impl Plugin for ScanCodesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KeyLabels>()
+            .add_systems(PreUpdate, learn_key_labels.in_set(ControlsSet::Gather));
+    }
+}
+
+/// Scan codes of the keys under the fingers in the home-row position, together with the finger
+/// on them: A S D F J K L ; and V N for pitch on QWERTY.
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+pub(crate) const HOME_ROW: Option<[(u32, &str); 10]> = Some([
+    (30, "L-pinky"),
+    (31, "L-ring"),
+    (32, "L-middle"),
+    (33, "L-index"),
+    (36, "R-index"),
+    (37, "R-middle"),
+    (38, "R-ring"),
+    (39, "R-pinky"),
+    (47, "L-index low"),
+    (49, "R-index low"),
+]);
+
+#[cfg(target_os = "macos")]
+pub(crate) const HOME_ROW: Option<[(u32, &str); 10]> = Some([
+    (0x00, "L-pinky"),
+    (0x01, "L-ring"),
+    (0x02, "L-middle"),
+    (0x03, "L-index"),
+    (0x26, "R-index"),
+    (0x28, "R-middle"),
+    (0x25, "R-ring"),
+    (0x29, "R-pinky"),
+    (0x09, "L-index low"),
+    (0x2D, "R-index low"),
+]);
+
+// The browser only reports layout dependent key codes, so the web build sticks to `KeyCode`s.
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+pub(crate) const HOME_ROW: Option<[(u32, &str); 10]> = None;
+
+/// What each scan code produces on the player's keyboard layout, learned from key presses.
+#[derive(Resource, Default)]
+pub(crate) struct KeyLabels(HashMap<u32, KeyCode>);
+
+impl KeyLabels {
+    /// Label for the key at `scan_code`. Winit can't tell what a key produces before it is
+    /// pressed, so until then home-row keys are named by the finger on them.
+    pub(crate) fn label(&self, scan_code: u32) -> String {
+        if let Some(key) = self.0.get(&scan_code) {
+            return key_label(*key);
+        }
+        match HOME_ROW
+            .into_iter()
+            .flatten()
+            .find(|(code, _)| *code == scan_code)
+        {
+            Some((_, finger)) => finger.to_string(),
+            None => format!("#{scan_code}"),
+        }
+    }
+}
+
+fn learn_key_labels(mut events: EventReader<KeyboardInput>, mut labels: ResMut<KeyLabels>) {
+    for event in events.read() {
+        if let Some(key) = event.key_code {
+            if labels.0.get(&event.scan_code) != Some(&key) {
+                labels.0.insert(event.scan_code, key);
+            }
+        }
+    }
+}
+
+/// The character printed on `key`, or its name for keys without one.
+pub(crate) fn key_label(key: KeyCode) -> String {
+    let label = match key {
+        KeyCode::Semicolon => ";",
+        KeyCode::Colon => ":",
+        KeyCode::Comma => ",",
+        KeyCode::Period => ".",
+        KeyCode::Slash => "/",
+        KeyCode::Backslash => "\\",
+        KeyCode::Apostrophe => "'",
+        KeyCode::Grave => "`",
+        KeyCode::Minus => "-",
+        KeyCode::Equals => "=",
+        KeyCode::Plus => "+",
+        KeyCode::BracketLeft => "[",
+        KeyCode::BracketRight => "]",
+        KeyCode::Key0 => "0",
+        KeyCode::Key1 => "1",
+        KeyCode::Key2 => "2",
+        KeyCode::Key3 => "3",
+        KeyCode::Key4 => "4",
+        KeyCode::Key5 => "5",
+        KeyCode::Key6 => "6",
+        KeyCode::Key7 => "7",
+        KeyCode::Key8 => "8",
+        KeyCode::Key9 => "9",
+        _ => return format!("{key:?}"),
+    };
+    label.to_string()
+}
@@ -9,6 +9,40 @@ pub struct ScoreText;
 pub struct ScoreState {
     pub distance: f32,
     pub hi_score: f32,
+    /// Extra score earned on top of the distance, e.g. for flying through storms.
+    pub bonus: f32,
+    /// Multiplier applied to the distance travelled, e.g. by the weather.
+    pub multiplier: f32,
+    pub typing: TypingStats,
+}
+
+/// How well the player types in typing mode.
+#[derive(Default)]
+pub struct TypingStats {
+    pub typed: u32,
+    pub mistakes: u32,
+    /// Seconds spent in typing mode.
+    pub secs: f32,
+}
+
+impl TypingStats {
+    pub fn accuracy(&self) -> f32 {
+        let total = self.typed + self.mistakes;
+        if total == 0 {
+            1.0
+        } else {
+            self.typed as f32 / total as f32
+        }
+    }
+
+    /// Words per minute, counting five characters as a word.
+    pub fn wpm(&self) -> f32 {
+        if self.secs <= 0.0 {
+            0.0
+        } else {
+            self.typed as f32 / 5.0 / (self.secs / 60.0)
+        }
+    }
 }
 
 #[derive(Component)]
@@ -100,6 +134,17 @@ fn setup_text(mut commands: Commands, asset_server: Res<AssetServer>) {
                     color: Color::GOLD,
                 },
             ),
+            // Typing stats, only filled in once something was typed.
+            TextSection::from_style(TextStyle {
+                font: bold_font.clone(),
+                font_size: 60.0,
+                ..default()
+            }),
+            TextSection::from_style(TextStyle {
+                font: medium_font.clone(),
+                font_size: 60.0,
+                color: Color::GOLD,
+            }),
         ]),
         ScoreText,
     ));
@@ -113,16 +158,29 @@ fn text_update_system(
 ) {
     for mut text in &mut query {
         for (tt, mut tst) in &mut target {
-            state.distance += (tt.translation - tst.last_pos).length();
+            let travelled = (tt.translation - tst.last_pos).length();
+            state.distance += travelled;
+            state.bonus += travelled * (state.multiplier - 1.0);
             tst.last_pos = tt.translation;
         }
         let coll = gamestate.waypoints_achieved_counter;
         let distance = state.distance;
-        let score = 2000.0 * coll as f32 + distance;
+        let score = 2000.0 * coll as f32 + distance + state.bonus;
         state.hi_score = state.hi_score.max(score);
         text.sections[1].value = format!("{:.2}\n", state.hi_score);
         text.sections[3].value = format!("{:.2}\n", score);
         text.sections[5].value = format!("{distance:.2}\n");
         text.sections[7].value = format!("{coll:.2}\n");
+        if state.typing.typed + state.typing.mistakes > 0 {
+            text.sections[8].value = "Accuracy/WPM: ".into();
+            text.sections[9].value = format!(
+                "{:.0}% / {:.0}\n",
+                state.typing.accuracy() * 100.0,
+                state.typing.wpm()
+            );
+        } else if !text.sections[8].value.is_empty() {
+            text.sections[8].value.clear();
+            text.sections[9].value.clear();
+        }
     }
 }
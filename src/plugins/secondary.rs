@@ -0,0 +1,114 @@
+use bevy::{prelude::*, utils::HashMap};
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{plugins::skeleton::BoundSkeleton, terrain_height, Birb};
+
+/// Procedural motion on top of the wing animation: the body banks into turns, the head keeps
+/// looking where the birb is going and the feet tuck in while it flies, coming out again when
+/// it nears the ground or slows down.
+pub(crate) struct SecondaryPlugin;
+
+impl Plugin for SecondaryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (bank_body, stabilize_head, tuck_feet));
+    }
+}
+
+/// Roll of the model per unit of yaw rate times speed.
+const BANK_FACTOR: f32 = 0.04;
+const MAX_BANK: f32 = 0.7;
+/// How far the head may turn away from the body.
+const MAX_HEAD_TURN: f32 = 1.0;
+/// Pitch of the feet when fully tucked.
+const FEET_TUCK: f32 = 1.2;
+/// Speeds between which the feet go from extended to tucked, so they come out when the birb
+/// hovers.
+const TUCK_SPEEDS: (f32, f32) = (3.0, 8.0);
+/// Heights above the ground between which the feet go from extended to tucked.
+const TUCK_HEIGHTS: (f32, f32) = (3.0, 10.0);
+/// Seconds of descent looked ahead, so the feet are out before the birb gets there.
+const LANDING_LOOKAHEAD: f32 = 0.5;
+/// How quickly the poses follow their targets, per second.
+const FOLLOW_RATE: f32 = 6.0;
+
+fn follow(time: &Time) -> f32 {
+    1.0 - (-FOLLOW_RATE * time.delta_seconds()).exp()
+}
+
+fn smoothstep(from: f32, to: f32, x: f32) -> f32 {
+    let t = ((x - from) / (to - from)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Rolls the model, not the physics body, into turns.
+fn bank_body(
+    time: Res<Time>,
+    birbs: Query<(&Transform, &LinearVelocity, &AngularVelocity, &Children), With<Birb>>,
+    mut models: Query<&mut Transform, (With<Handle<Scene>>, Without<Birb>)>,
+) {
+    for (transform, velocity, angular_velocity, children) in &birbs {
+        let yaw_rate = angular_velocity.dot(transform.up());
+        // Turning left (positive yaw) lowers the left wing.
+        let bank = (-yaw_rate * velocity.length() * BANK_FACTOR).clamp(-MAX_BANK, MAX_BANK);
+        let target = Quat::from_rotation_z(bank);
+        let mut models = models.iter_many_mut(children);
+        while let Some(mut model) = models.fetch_next() {
+            model.rotation = model.rotation.slerp(target, follow(&time));
+        }
+    }
+}
+
+fn stabilize_head(
+    time: Res<Time>,
+    birbs: Query<(&LinearVelocity, &BoundSkeleton), With<Birb>>,
+    parents: Query<&Parent>,
+    globals: Query<&GlobalTransform>,
+    mut joints: Query<&mut Transform>,
+    mut base_rots: Local<HashMap<Entity, Quat>>,
+) {
+    for (velocity, skeleton) in &birbs {
+        let Some(head) = skeleton.head else {
+            continue;
+        };
+        let Ok(mut transform) = joints.get_mut(head) else {
+            continue;
+        };
+        let base = *base_rots.entry(head).or_insert(transform.rotation);
+        let parent_rot = parents
+            .get(head)
+            .and_then(|parent| globals.get(parent.get()))
+            .map_or(Quat::IDENTITY, |global| global.compute_transform().rotation);
+
+        let mut target = base;
+        if let Some(direction) = (parent_rot.inverse() * velocity.0).try_normalize() {
+            let turn = Quat::from_rotation_arc(base * Vec3::Z, direction);
+            let (axis, angle) = turn.to_axis_angle();
+            target = Quat::from_axis_angle(axis, angle.min(MAX_HEAD_TURN)) * base;
+        }
+        transform.rotation = transform.rotation.slerp(target, follow(&time));
+    }
+}
+
+fn tuck_feet(
+    time: Res<Time>,
+    birbs: Query<(&Transform, &LinearVelocity, &BoundSkeleton), With<Birb>>,
+    mut joints: Query<&mut Transform, Without<Birb>>,
+    mut base_rots: Local<HashMap<Entity, Quat>>,
+) {
+    for (transform, velocity, skeleton) in &birbs {
+        let position = transform.translation;
+        let height = position.y + velocity.y.min(0.0) * LANDING_LOOKAHEAD
+            - terrain_height(position.x, position.z);
+        let tuck = smoothstep(TUCK_SPEEDS.0, TUCK_SPEEDS.1, velocity.length())
+            * smoothstep(TUCK_HEIGHTS.0, TUCK_HEIGHTS.1, height)
+            * FEET_TUCK;
+        for &foot in &skeleton.feet {
+            let Ok(mut transform) = joints.get_mut(foot) else {
+                continue;
+            };
+            let base = *base_rots.entry(foot).or_insert(transform.rotation);
+            let target = base * Quat::from_rotation_x(tuck);
+            transform.rotation = transform.rotation.slerp(target, follow(&time));
+        }
+    }
+}
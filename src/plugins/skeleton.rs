@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+
+use crate::{Birb, BirbState};
+
+/// Binds the wing joints of the birb's model by the names of their glTF nodes.
+pub(crate) struct SkeletonPlugin;
+
+impl Plugin for SkeletonPlugin {
+    fn build(&self, app: &mut App) {
+        // Scenes are spawned right before `PostUpdate`, so the joints get bound the same frame.
+        app.add_systems(PostUpdate, bind_skeleton);
+    }
+}
+
+/// Which glTF nodes of a model are the wing joints.
+#[derive(Component, Clone)]
+pub(crate) struct SkeletonMapping {
+    /// Node names in `BirbState::angles` order: the right wing (as seen by the player) from the
+    /// tip inwards, followed by the left wing from the body outwards.
+    pub(crate) wing_joints: [&'static str; 8],
+    /// Optional joint that keeps looking where the birb is going.
+    pub(crate) head: Option<&'static str>,
+    /// Optional joints that tuck in during flight.
+    pub(crate) feet: &'static [&'static str],
+}
+
+/// The optional joints of a birb that could be found in its model.
+#[derive(Component)]
+pub(crate) struct BoundSkeleton {
+    pub(crate) head: Option<Entity>,
+    pub(crate) feet: Vec<Entity>,
+}
+
+pub(crate) const BIRB2_SKELETON: SkeletonMapping = SkeletonMapping {
+    wing_joints: [
+        "Bone.003.R.002",
+        "Bone.003.R",
+        "Bone.002.R",
+        "Bone.001.R",
+        "Bone.001.L",
+        "Bone.002.L",
+        "Bone.003.L",
+        "Bone.003.L.002",
+    ],
+    head: Some("Head"),
+    feet: &["Foot.L", "Foot.R"],
+};
+
+/// Marks a birb whose skeleton has been checked, whether it could be bound or not.
+#[derive(Component)]
+struct SkeletonChecked;
+
+fn bind_skeleton(
+    mut commands: Commands,
+    birbs: Query<(Entity, &SkeletonMapping), (With<Birb>, Without<SkeletonChecked>)>,
+    children: Query<&Children>,
+    names: Query<(Entity, &Name)>,
+    mut birb_state: ResMut<BirbState>,
+) {
+    for (birb, mapping) in &birbs {
+        let nodes: Vec<_> = children
+            .iter_descendants(birb)
+            .filter_map(|entity| names.get(entity).ok())
+            .collect();
+        // The scene hasn't been spawned yet.
+        if nodes.is_empty() {
+            continue;
+        }
+        commands.entity(birb).insert(SkeletonChecked);
+
+        let find = |joint: &str| {
+            nodes
+                .iter()
+                .find(|(_, name)| name.as_str() == joint)
+                .map(|(entity, _)| *entity)
+        };
+        let missing: Vec<_> = mapping
+            .wing_joints
+            .iter()
+            .filter(|joint| find(joint).is_none())
+            .collect();
+        if !missing.is_empty() {
+            let found: Vec<_> = nodes.iter().map(|(_, name)| name.as_str()).collect();
+            error!(
+                "Birb model is missing the wing joints {missing:?}, its wings won't move. \
+                 Nodes in the model: {found:?}"
+            );
+            continue;
+        }
+
+        birb_state.wing_joints = Some(mapping.wing_joints.iter().filter_map(|j| find(j)).collect());
+        birb_state.original_rots = None;
+
+        let optional = mapping.head.iter().chain(mapping.feet);
+        for joint in optional.filter(|joint| find(joint).is_none()) {
+            warn!("Birb model is missing the joint {joint}, it won't be animated");
+        }
+        commands.entity(birb).insert(BoundSkeleton {
+            head: mapping.head.and_then(find),
+            feet: mapping.feet.iter().filter_map(|j| find(j)).collect(),
+        });
+    }
+}
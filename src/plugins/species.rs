@@ -0,0 +1,210 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config,
+    plugins::skeleton::{SkeletonMapping, BIRB2_SKELETON},
+    AppState,
+};
+
+const CONFIG_FILE: &str = "species.ron";
+
+/// The catalogue of birds to fly and the screen to pick one before a run.
+pub(crate) struct SpeciesPlugin;
+
+impl Plugin for SpeciesPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.world.contains_resource::<SelectedSpecies>() {
+            app.insert_resource(config::load::<SelectedSpecies>(CONFIG_FILE).unwrap_or_default());
+        }
+        app.add_systems(OnEnter(AppState::SelectSpecies), setup_species_screen)
+            .add_systems(OnExit(AppState::SelectSpecies), despawn_species_screen)
+            .add_systems(
+                Update,
+                species_buttons.run_if(in_state(AppState::SelectSpecies)),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub(crate) enum Species {
+    Sparrow,
+    #[default]
+    Gull,
+    Albatross,
+}
+
+/// Everything that makes one species look and fly differently.
+pub(crate) struct SpeciesStats {
+    pub(crate) name: &'static str,
+    pub(crate) description: &'static str,
+    pub(crate) scene: &'static str,
+    pub(crate) skeleton: SkeletonMapping,
+    /// Scale of the model.
+    pub(crate) scale: f32,
+    pub(crate) collider_radius: f32,
+    /// Length of each wing segment, from the body outwards.
+    pub(crate) segment_lengths: [f32; 4],
+    /// Area of each wing segment, from the body outwards.
+    pub(crate) segment_areas: [f32; 4],
+    /// Multiplier on the tuned `FlightParams::linear_damping`.
+    pub(crate) linear_damping: f32,
+    /// Multiplier on the tuned `FlightParams::angular_damping`.
+    pub(crate) angular_damping: f32,
+    /// Seconds between two poops.
+    pub(crate) poop_cooldown: f64,
+}
+
+impl Species {
+    pub(crate) const ALL: [Species; 3] = [Species::Sparrow, Species::Gull, Species::Albatross];
+
+    pub(crate) fn stats(self) -> SpeciesStats {
+        match self {
+            Species::Sparrow => SpeciesStats {
+                name: "Sparrow",
+                description: "Small and twitchy, poops a lot",
+                scene: "models/birb2.gltf#Scene0",
+                skeleton: BIRB2_SKELETON,
+                scale: 0.6,
+                collider_radius: 0.3,
+                segment_lengths: [0.68, 1.54, 1.34, 0.77],
+                segment_areas: [1.2, 0.6, 0.3, 0.15],
+                linear_damping: 1.2,
+                angular_damping: 0.7,
+                poop_cooldown: 0.25,
+            },
+            Species::Gull => SpeciesStats {
+                name: "Gull",
+                description: "All-rounder",
+                scene: "models/birb2.gltf#Scene0",
+                skeleton: BIRB2_SKELETON,
+                scale: 1.0,
+                collider_radius: 0.5,
+                segment_lengths: [1.14, 2.57, 2.24, 1.29],
+                segment_areas: [2.0, 1.0, 0.5, 0.25],
+                linear_damping: 1.0,
+                angular_damping: 1.0,
+                poop_cooldown: 0.5,
+            },
+            Species::Albatross => SpeciesStats {
+                name: "Albatross",
+                description: "Huge wings, glides forever, turns slowly",
+                scene: "models/birb2.gltf#Scene0",
+                skeleton: BIRB2_SKELETON,
+                scale: 1.6,
+                collider_radius: 0.8,
+                segment_lengths: [1.4, 3.6, 3.4, 2.2],
+                segment_areas: [2.5, 1.6, 0.9, 0.5],
+                linear_damping: 0.5,
+                angular_damping: 1.5,
+                poop_cooldown: 1.0,
+            },
+        }
+    }
+}
+
+/// The species flown in this run.
+#[derive(Resource, Clone, Copy, Default, Serialize, Deserialize)]
+pub(crate) struct SelectedSpecies(pub(crate) Species);
+
+#[derive(Component)]
+struct SpeciesScreen;
+
+#[derive(Component)]
+struct SpeciesButton(Species);
+
+fn setup_species_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    selected: Res<SelectedSpecies>,
+) {
+    let title_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 40.0,
+        color: Color::WHITE,
+    };
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+        font_size: 18.0,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(10.0),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+                ..default()
+            },
+            SpeciesScreen,
+        ))
+        .with_children(|screen| {
+            screen.spawn(TextBundle::from_section(
+                "Pick your bird",
+                title_style.clone(),
+            ));
+            for species in Species::ALL {
+                let stats = species.stats();
+                let color = if species == selected.0 {
+                    Color::rgb(0.35, 0.35, 0.2)
+                } else {
+                    Color::DARK_GRAY
+                };
+                screen
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(420.0),
+                                flex_direction: FlexDirection::Column,
+                                padding: UiRect::all(Val::Px(10.0)),
+                                ..default()
+                            },
+                            background_color: color.into(),
+                            ..default()
+                        },
+                        SpeciesButton(species),
+                    ))
+                    .with_children(|b| {
+                        b.spawn(TextBundle::from_section(
+                            stats.name,
+                            TextStyle {
+                                font_size: 28.0,
+                                ..title_style.clone()
+                            },
+                        ));
+                        b.spawn(TextBundle::from_section(
+                            stats.description,
+                            text_style.clone(),
+                        ));
+                    });
+            }
+        });
+}
+
+fn despawn_species_screen(mut commands: Commands, screens: Query<Entity, With<SpeciesScreen>>) {
+    for screen in &screens {
+        commands.entity(screen).despawn_recursive();
+    }
+}
+
+fn species_buttons(
+    buttons: Query<(&Interaction, &SpeciesButton), Changed<Interaction>>,
+    mut selected: ResMut<SelectedSpecies>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction == Interaction::Pressed {
+            selected.0 = button.0;
+            config::save(CONFIG_FILE, &*selected);
+            next_state.set(AppState::InGame);
+        }
+    }
+}
@@ -0,0 +1,375 @@
+use bevy::{prelude::*, transform::TransformSystem};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config,
+    plugins::{
+        courses::{CourseRun, Courses, SelectedCourse},
+        replay::{RngStream, RunSeed},
+        species::{SelectedSpecies, Species},
+        wind::WindField,
+    },
+    AppState, Birb, BirbState, Respawned,
+};
+
+const GHOST_DIR: &str = "ghosts";
+
+/// Flies a course in a world seeded by the course, against a ghost of the best run so far.
+pub(crate) struct TimeTrialPlugin;
+
+impl Plugin for TimeTrialPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnExit(AppState::SelectSpecies),
+            (seed_time_trial.in_set(TimeTrialSeedSet), start_time_trial),
+        )
+        .add_systems(
+            Update,
+            (prepare_ghost, update_time_trial_text).run_if(resource_exists::<TimeTrial>()),
+        )
+        .add_systems(
+            PostUpdate,
+            (record_ghost, move_ghost)
+                .chain()
+                .before(TransformSystem::TransformPropagate)
+                .run_if(resource_exists::<TimeTrial>().and_then(in_state(AppState::InGame))),
+        );
+    }
+}
+
+/// Replaces the [`RunSeed`] with the one of the time trial's course. Everything seeded when the
+/// run starts has to come after it.
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+pub(crate) struct TimeTrialSeedSet;
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct GhostFrame {
+    /// Seconds since the start of the course.
+    time: f32,
+    translation: Vec3,
+    rotation: Quat,
+    /// Wing joint angles in `BirbState::angles` order.
+    angles: [f32; 8],
+}
+
+/// The trajectory of a run through a course, one frame per tick.
+#[derive(Serialize, Deserialize, Clone)]
+struct Ghost {
+    seed: u64,
+    species: Species,
+    /// Time the course was finished in.
+    time: f32,
+    frames: Vec<GhostFrame>,
+}
+
+impl Ghost {
+    /// Where the ghost was `time` seconds into its run, `None` once it has finished.
+    fn sample(&self, time: f32) -> Option<GhostFrame> {
+        let next = self.frames.partition_point(|frame| frame.time <= time);
+        let after = self.frames.get(next)?;
+        let Some(before) = next.checked_sub(1).map(|i| self.frames[i]) else {
+            return Some(*after);
+        };
+        let t = ((time - before.time) / (after.time - before.time).max(f32::EPSILON)).min(1.0);
+        Some(GhostFrame {
+            time,
+            translation: before.translation.lerp(after.translation, t),
+            rotation: before.rotation.slerp(after.rotation, t),
+            angles: std::array::from_fn(|i| {
+                before.angles[i] + (after.angles[i] - before.angles[i]) * t
+            }),
+        })
+    }
+
+    /// The time at which the ghost was closest to `position`, looking around `time`.
+    fn time_near(&self, position: Vec3, time: f32) -> Option<f32> {
+        let from = self
+            .frames
+            .partition_point(|frame| frame.time < time - DELTA_WINDOW);
+        let to = self
+            .frames
+            .partition_point(|frame| frame.time <= time + DELTA_WINDOW);
+        self.frames[from..to]
+            .iter()
+            .min_by(|a, b| {
+                let a = a.translation.distance_squared(position);
+                let b = b.translation.distance_squared(position);
+                a.total_cmp(&b)
+            })
+            .map(|frame| frame.time)
+    }
+}
+
+/// Seconds around the current time searched for the ghost's matching position.
+const DELTA_WINDOW: f32 = 10.0;
+const GHOST_ALPHA: f32 = 0.35;
+const AHEAD_COLOR: Color = Color::rgb(0.4, 1.0, 0.4);
+const BEHIND_COLOR: Color = Color::rgb(1.0, 0.4, 0.4);
+
+#[derive(Resource)]
+struct TimeTrial {
+    /// File the best run is saved to.
+    ghost_file: String,
+    seed: u64,
+    best: Option<Ghost>,
+    /// A new best that replaces the ghost on the next attempt.
+    new_best: Option<Ghost>,
+    recording: Vec<GhostFrame>,
+    /// Whether the finish of the current attempt has been handled.
+    finished: bool,
+}
+
+/// A translucent birb flying the best run.
+#[derive(Component)]
+struct GhostBirb {
+    species: Species,
+    /// The wing joints and their rest rotations, once the model has been spawned.
+    joints: Option<Vec<(Entity, Quat)>>,
+}
+
+#[derive(Component)]
+struct TimeTrialText;
+
+fn seed_time_trial(
+    courses: Res<Courses>,
+    selected: Res<SelectedCourse>,
+    mut seed: ResMut<RunSeed>,
+    mut wind: ResMut<WindField>,
+) {
+    let Some((_, course)) = selected.time_trial(&courses) else {
+        return;
+    };
+    *seed = RunSeed(course.seed());
+    *wind = WindField::new(seed.0 as u32, seed.rng(RngStream::Wind));
+}
+
+fn start_time_trial(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    courses: Res<Courses>,
+    selected: Res<SelectedCourse>,
+) {
+    let Some((file, course)) = selected.time_trial(&courses) else {
+        return;
+    };
+    let ghost_file = format!("ghost-{file}");
+    let seed = course.seed();
+    // Ghosts flown in another world can't be raced fairly.
+    let best = config::load_from::<Ghost>(GHOST_DIR, &ghost_file).filter(|g| g.seed == seed);
+    if let Some(best) = &best {
+        spawn_ghost(&mut commands, &asset_server, best.species);
+    }
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                font_size: 28.0,
+                color: Color::WHITE,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        })
+        .with_text_alignment(TextAlignment::Center),
+        TimeTrialText,
+    ));
+    commands.insert_resource(TimeTrial {
+        ghost_file,
+        seed,
+        best,
+        new_best: None,
+        recording: Vec::new(),
+        finished: false,
+    });
+}
+
+fn spawn_ghost(commands: &mut Commands, asset_server: &AssetServer, species: Species) {
+    let stats = species.stats();
+    commands
+        .spawn((
+            SpatialBundle::default(),
+            GhostBirb {
+                species,
+                joints: None,
+            },
+        ))
+        .with_children(|ghost| {
+            ghost.spawn(SceneBundle {
+                scene: asset_server.load(stats.scene),
+                transform: Transform::from_scale(Vec3::splat(stats.scale)),
+                ..default()
+            });
+        });
+}
+
+/// Finds the ghost's wing joints and makes it translucent once its scene has been spawned.
+fn prepare_ghost(
+    mut ghosts: Query<(Entity, &mut GhostBirb)>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+    transforms: Query<&Transform>,
+    mut parts: Query<&mut Handle<StandardMaterial>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (entity, mut ghost) in &mut ghosts {
+        if ghost.joints.is_some() {
+            continue;
+        }
+        let descendants: Vec<Entity> = children.iter_descendants(entity).collect();
+        // The scene hasn't been spawned yet.
+        if !descendants.iter().any(|e| names.contains(*e)) {
+            continue;
+        }
+
+        let skeleton = ghost.species.stats().skeleton;
+        let joints = skeleton
+            .wing_joints
+            .iter()
+            .filter_map(|joint| {
+                let entity = descendants
+                    .iter()
+                    .find(|e| names.get(**e).is_ok_and(|name| name.as_str() == *joint))?;
+                Some((*entity, transforms.get(*entity).ok()?.rotation))
+            })
+            .collect();
+        ghost.joints = Some(joints);
+
+        let mut parts = parts.iter_many_mut(&descendants);
+        while let Some(mut material) = parts.fetch_next() {
+            let mut translucent = materials.get(&*material).cloned().unwrap_or_default();
+            translucent.base_color.set_a(GHOST_ALPHA);
+            translucent.alpha_mode = AlphaMode::Blend;
+            *material = materials.add(translucent);
+        }
+    }
+}
+
+fn record_ghost(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    run: Option<Res<CourseRun>>,
+    mut time_trial: ResMut<TimeTrial>,
+    mut respawned: EventReader<Respawned>,
+    birb_state: Res<BirbState>,
+    species: Res<SelectedSpecies>,
+    birbs: Query<&Transform, With<Birb>>,
+    ghosts: Query<Entity, With<GhostBirb>>,
+) {
+    // Respawning restarts the course: race against the best run from now on.
+    let restarted = respawned.read().count() > 0;
+    let (Some(run), Ok(birb)) = (run, birbs.get_single()) else {
+        return;
+    };
+    let time_trial = &mut *time_trial;
+
+    if restarted {
+        time_trial.recording.clear();
+        time_trial.finished = false;
+        if let Some(new_best) = time_trial.new_best.take() {
+            for ghost in &ghosts {
+                commands.entity(ghost).despawn_recursive();
+            }
+            spawn_ghost(&mut commands, &asset_server, new_best.species);
+            time_trial.best = Some(new_best);
+        }
+    }
+
+    if run.is_finished() {
+        if !time_trial.finished {
+            time_trial.finished = true;
+            let beaten = time_trial
+                .best
+                .as_ref()
+                .is_none_or(|best| run.elapsed < best.time);
+            if beaten {
+                let ghost = Ghost {
+                    seed: time_trial.seed,
+                    species: species.0,
+                    time: run.elapsed,
+                    frames: time_trial.recording.clone(),
+                };
+                config::save_to(GHOST_DIR, &time_trial.ghost_file, &ghost);
+                time_trial.new_best = Some(ghost);
+            }
+        }
+        return;
+    }
+
+    time_trial.recording.push(GhostFrame {
+        time: run.elapsed,
+        translation: birb.translation,
+        rotation: birb.rotation,
+        angles: std::array::from_fn(|i| birb_state.angles[i]),
+    });
+}
+
+fn move_ghost(
+    run: Option<Res<CourseRun>>,
+    time_trial: Res<TimeTrial>,
+    mut ghosts: Query<(&GhostBirb, &mut Transform, &mut Visibility)>,
+    mut joints: Query<&mut Transform, Without<GhostBirb>>,
+) {
+    let (Some(run), Some(best)) = (run, &time_trial.best) else {
+        return;
+    };
+    let frame = best.sample(run.elapsed);
+    for (ghost, mut transform, mut visibility) in &mut ghosts {
+        let Some(frame) = frame else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+        transform.translation = frame.translation;
+        transform.rotation = frame.rotation;
+        for ((joint, original), angle) in ghost.joints.iter().flatten().zip(frame.angles) {
+            if let Ok(mut joint) = joints.get_mut(*joint) {
+                joint.rotation = *original * Quat::from_rotation_x(angle);
+            }
+        }
+    }
+}
+
+fn update_time_trial_text(
+    run: Option<Res<CourseRun>>,
+    time_trial: Res<TimeTrial>,
+    birbs: Query<&Transform, With<Birb>>,
+    mut texts: Query<&mut Text, With<TimeTrialText>>,
+) {
+    let (Some(run), Ok(mut text)) = (run, texts.get_single_mut()) else {
+        return;
+    };
+    let best = time_trial.best.as_ref();
+    let best_time = best.map_or("--".to_string(), |b| format!("{:.2}s", b.time));
+    let section = &mut text.sections[0];
+    section.style.color = Color::WHITE;
+
+    if run.is_finished() {
+        section.value = match (&time_trial.new_best, best) {
+            (Some(_), _) => format!("New best! {:.2}s", run.elapsed),
+            (None, Some(best)) => format!("{:.2}s, best {:.2}s", run.elapsed, best.time),
+            (None, None) => format!("{:.2}s", run.elapsed),
+        };
+        return;
+    }
+    let delta = best.zip(birbs.get_single().ok()).and_then(|(best, birb)| {
+        let ghost_time = best.time_near(birb.translation, run.elapsed)?;
+        Some(run.elapsed - ghost_time)
+    });
+    section.value = match delta {
+        Some(delta) => {
+            section.style.color = if delta <= 0.0 {
+                AHEAD_COLOR
+            } else {
+                BEHIND_COLOR
+            };
+            format!("Best {best_time}  {delta:+.2}s")
+        }
+        None => format!("Best {best_time}"),
+    };
+}
@@ -0,0 +1,161 @@
+use bevy::prelude::*;
+
+use crate::plugins::controls::{ControlsSet, InputAction, PendingActions};
+
+/// On-screen touch zones for phones and tablets. They only show up once the screen has been
+/// touched, and every finger on a zone presses its action.
+pub(crate) struct TouchPlugin;
+
+impl Plugin for TouchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_touch_zones).add_systems(
+            PreUpdate,
+            (show_touch_zones, touch_zones).in_set(ControlsSet::Gather),
+        );
+    }
+}
+
+#[derive(Component)]
+struct TouchControls;
+
+#[derive(Component)]
+struct TouchZone(InputAction);
+
+const IDLE_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.15);
+const PRESSED_COLOR: Color = Color::rgba(1.0, 0.843, 0.0, 0.4);
+const FONT_SIZE: f32 = 24.0;
+
+fn setup_touch_zones(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: FONT_SIZE,
+        color: Color::WHITE,
+    };
+    let zone = |width: f32, height: f32| NodeBundle {
+        style: Style {
+            width: Val::Vw(width),
+            height: Val::Vh(height),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: IDLE_COLOR.into(),
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(0.0),
+                    width: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Vh(2.0),
+                    padding: UiRect::all(Val::Vh(2.0)),
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            TouchControls,
+        ))
+        .with_children(|root| {
+            // Pitch on the left, poop on the right.
+            root.spawn(NodeBundle {
+                style: Style {
+                    justify_content: JustifyContent::SpaceBetween,
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|row| {
+                row.spawn(NodeBundle {
+                    style: Style {
+                        column_gap: Val::Vw(1.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|pitch| {
+                    for (action, label) in [
+                        (InputAction::PitchUp, "Up"),
+                        (InputAction::PitchDown, "Down"),
+                    ] {
+                        pitch
+                            .spawn((zone(10.0, 10.0), TouchZone(action)))
+                            .with_children(|z| {
+                                z.spawn(TextBundle::from_section(label, text_style.clone()));
+                            });
+                    }
+                });
+                row.spawn((zone(12.0, 10.0), TouchZone(InputAction::Poop)))
+                    .with_children(|z| {
+                        z.spawn(TextBundle::from_section("Poop", text_style.clone()));
+                    });
+            });
+
+            // The wing joints laid out like the home row: outer joints on the outside.
+            root.spawn(NodeBundle {
+                style: Style {
+                    justify_content: JustifyContent::SpaceBetween,
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|row| {
+                for wing in [
+                    [3, 2, 1, 0].map(InputAction::LeftWingJoint),
+                    [0, 1, 2, 3].map(InputAction::RightWingJoint),
+                ] {
+                    row.spawn(NodeBundle {
+                        style: Style {
+                            column_gap: Val::Vw(1.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|side| {
+                        for action in wing {
+                            side.spawn((zone(10.0, 22.0), TouchZone(action)));
+                        }
+                    });
+                }
+            });
+        });
+}
+
+fn show_touch_zones(
+    touches: Res<Touches>,
+    mut controls: Query<&mut Visibility, With<TouchControls>>,
+) {
+    if touches.any_just_pressed() {
+        for mut visibility in &mut controls {
+            *visibility = Visibility::Visible;
+        }
+    }
+}
+
+fn touch_zones(
+    touches: Res<Touches>,
+    mut zones: Query<(
+        &Node,
+        &GlobalTransform,
+        &ViewVisibility,
+        &TouchZone,
+        &mut BackgroundColor,
+    )>,
+    mut pending: ResMut<PendingActions>,
+) {
+    for (node, transform, visibility, zone, mut color) in &mut zones {
+        let rect = node.logical_rect(transform);
+        let touched = visibility.get() && touches.iter().any(|t| rect.contains(t.position()));
+        if touched {
+            pending.press(zone.0);
+        }
+        let wanted = if touched { PRESSED_COLOR } else { IDLE_COLOR };
+        if color.0 != wanted {
+            color.0 = wanted;
+        }
+    }
+}
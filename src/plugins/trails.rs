@@ -0,0 +1,273 @@
+use std::collections::VecDeque;
+
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        view::NoFrustumCulling,
+    },
+};
+use bevy_xpbd_3d::prelude::*;
+use rand::Rng;
+
+use crate::{AppState, Birb, BirbState};
+
+/// Vapour trails behind the wingtips that grow with airspeed, and puffs of air on strong
+/// down-strokes.
+///
+/// Each trail is a single camera-facing ribbon mesh rebuilt every frame and the puffs share one
+/// mesh and material, so this stays cheap enough for the wasm build.
+pub(crate) struct TrailsPlugin;
+
+impl Plugin for TrailsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, trails_setup).add_systems(
+            Update,
+            (update_trails, spawn_puffs, fade_puffs).run_if(in_state(AppState::InGame)),
+        );
+    }
+}
+
+/// The outermost joint of each wing, as indices into `BirbState::wing_joints`.
+const WINGTIPS: [usize; 2] = [0, 7];
+/// The joints of each wing, as indices into `BirbState::angular_velocity`.
+const WINGS: [[usize; 4]; 2] = [[0, 1, 2, 3], [4, 5, 6, 7]];
+
+const SAMPLE_INTERVAL: f32 = 0.03;
+const TRAIL_LIFETIME: f32 = 0.6;
+/// Airspeeds between which the trails fade in.
+const TRAIL_SPEEDS: (f32, f32) = (8.0, 25.0);
+const MAX_TRAIL_WIDTH: f32 = 0.12;
+const MAX_TRAIL_ALPHA: f32 = 0.5;
+
+/// Summed angular velocity of a wing's joints that counts as a strong down-stroke.
+const PUFF_STROKE: f32 = 12.0;
+const PUFFS_PER_STROKE: usize = 3;
+const MAX_PUFFS: usize = 30;
+const PUFF_LIFETIME: f32 = 0.5;
+const PUFF_SIZE: f32 = 0.25;
+
+#[derive(Resource)]
+struct PuffAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+struct TrailPoint {
+    position: Vec3,
+    age: f32,
+    /// Fraction of the full width and opacity, from the airspeed when it was left behind.
+    strength: f32,
+}
+
+#[derive(Component)]
+struct Trail {
+    wing: usize,
+    /// Newest point first.
+    points: VecDeque<TrailPoint>,
+    since_sample: f32,
+}
+
+#[derive(Component)]
+struct Puff {
+    age: f32,
+    velocity: Vec3,
+}
+
+fn trails_setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let trail_material = materials.add(StandardMaterial {
+        base_color: Color::WHITE,
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        double_sided: true,
+        cull_mode: None,
+        ..default()
+    });
+    for wing in 0..2 {
+        commands.spawn((
+            PbrBundle {
+                mesh: meshes.add(ribbon_mesh(Vec::new(), Vec::new())),
+                material: trail_material.clone(),
+                ..default()
+            },
+            // The ribbon is built in world space, so its bounding box would never be right.
+            NoFrustumCulling,
+            Trail {
+                wing,
+                points: VecDeque::new(),
+                since_sample: 0.0,
+            },
+        ));
+    }
+
+    commands.insert_resource(PuffAssets {
+        mesh: meshes.add(Mesh::from(shape::UVSphere {
+            radius: PUFF_SIZE,
+            sectors: 6,
+            stacks: 4,
+        })),
+        material: materials.add(StandardMaterial {
+            base_color: Color::rgba(1.0, 1.0, 1.0, 0.4),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+fn ribbon_mesh(positions: Vec<[f32; 3]>, colors: Vec<[f32; 4]>) -> Mesh {
+    let segments = (positions.len() / 2).saturating_sub(1) as u32;
+    let indices = (0..segments)
+        .flat_map(|i| {
+            let a = i * 2;
+            [a, a + 1, a + 2, a + 1, a + 3, a + 2]
+        })
+        .collect();
+    Mesh::new(PrimitiveTopology::TriangleList)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+        .with_indices(Some(Indices::U32(indices)))
+}
+
+fn update_trails(
+    time: Res<Time>,
+    birb_state: Res<BirbState>,
+    birbs: Query<&LinearVelocity, With<Birb>>,
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    global_transforms: Query<&GlobalTransform>,
+    mut trails: Query<(&mut Trail, &Handle<Mesh>)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let (Some(wing_joints), Ok(velocity)) = (&birb_state.wing_joints, birbs.get_single()) else {
+        return;
+    };
+    let camera = cameras.iter().next().map(|c| c.translation());
+    let speed = velocity.length();
+    let strength = ((speed - TRAIL_SPEEDS.0) / (TRAIL_SPEEDS.1 - TRAIL_SPEEDS.0)).clamp(0.0, 1.0);
+
+    for (mut trail, mesh) in &mut trails {
+        let Some(tip) = global_transforms
+            .get(wing_joints[WINGTIPS[trail.wing]])
+            .ok()
+            .map(|t| t.translation())
+        else {
+            continue;
+        };
+
+        for point in &mut trail.points {
+            point.age += time.delta_seconds();
+        }
+        while trail.points.back().is_some_and(|p| p.age > TRAIL_LIFETIME) {
+            trail.points.pop_back();
+        }
+        trail.since_sample += time.delta_seconds();
+        // The newest point follows the tip until it is left behind.
+        if trail.since_sample >= SAMPLE_INTERVAL || trail.points.is_empty() {
+            trail.since_sample = 0.0;
+            trail.points.push_front(TrailPoint {
+                position: tip,
+                age: 0.0,
+                strength,
+            });
+        } else if let Some(newest) = trail.points.front_mut() {
+            newest.position = tip;
+            newest.strength = strength;
+        }
+
+        let mut positions = Vec::with_capacity(trail.points.len() * 2);
+        let mut colors = Vec::with_capacity(trail.points.len() * 2);
+        let points = trail.points.make_contiguous();
+        for (i, point) in points.iter().enumerate() {
+            let along = match (points.get(i.wrapping_sub(1)), points.get(i + 1)) {
+                (Some(newer), _) => newer.position - point.position,
+                (None, Some(older)) => point.position - older.position,
+                (None, None) => Vec3::ZERO,
+            };
+            let to_camera = camera.map_or(Vec3::Y, |c| c - point.position);
+            let fade = 1.0 - point.age / TRAIL_LIFETIME;
+            let side = along.cross(to_camera).normalize_or_zero()
+                * MAX_TRAIL_WIDTH
+                * point.strength
+                * fade;
+            positions.push((point.position + side).to_array());
+            positions.push((point.position - side).to_array());
+            let color = [1.0, 1.0, 1.0, MAX_TRAIL_ALPHA * point.strength * fade];
+            colors.extend([color, color]);
+        }
+        if let Some(mesh) = meshes.get_mut(mesh) {
+            *mesh = ribbon_mesh(positions, colors);
+        }
+    }
+}
+
+fn spawn_puffs(
+    mut commands: Commands,
+    assets: Res<PuffAssets>,
+    birb_state: Res<BirbState>,
+    birbs: Query<&LinearVelocity, With<Birb>>,
+    global_transforms: Query<&GlobalTransform>,
+    puffs: Query<(), With<Puff>>,
+    mut was_stroking: Local<[bool; 2]>,
+) {
+    let (Some(wing_joints), Ok(velocity)) = (&birb_state.wing_joints, birbs.get_single()) else {
+        return;
+    };
+    let mut room = MAX_PUFFS.saturating_sub(puffs.iter().count());
+    let mut rng = rand::thread_rng();
+    for (wing, joints) in WINGS.iter().enumerate() {
+        let stroke: f32 = joints.iter().map(|&j| birb_state.angular_velocity[j]).sum();
+        let stroking = stroke > PUFF_STROKE;
+        // One burst at the start of every down-stroke.
+        let starting = stroking && !was_stroking[wing];
+        was_stroking[wing] = stroking;
+        if !starting {
+            continue;
+        }
+        let Ok(tip) = global_transforms.get(wing_joints[WINGTIPS[wing]]) else {
+            continue;
+        };
+        for _ in 0..PUFFS_PER_STROKE.min(room) {
+            let jitter = Vec3::new(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..0.0),
+                rng.gen_range(-1.0..1.0),
+            );
+            commands.spawn((
+                PbrBundle {
+                    mesh: assets.mesh.clone(),
+                    material: assets.material.clone(),
+                    transform: Transform::from_translation(tip.translation() + jitter * 0.2),
+                    ..default()
+                },
+                Puff {
+                    age: 0.0,
+                    // Pushed down by the wing, left behind by the birb.
+                    velocity: jitter - velocity.0 * 0.3,
+                },
+            ));
+            room -= 1;
+        }
+    }
+}
+
+fn fade_puffs(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut puffs: Query<(Entity, &mut Transform, &mut Puff)>,
+) {
+    for (entity, mut transform, mut puff) in &mut puffs {
+        puff.age += time.delta_seconds();
+        if puff.age > PUFF_LIFETIME {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation += puff.velocity * time.delta_seconds();
+        // Expand while thinning out, the material is shared so it can't fade.
+        let life = puff.age / PUFF_LIFETIME;
+        transform.scale = Vec3::splat((1.0 + life * 2.0) * (1.0 - life));
+    }
+}
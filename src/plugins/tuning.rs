@@ -0,0 +1,416 @@
+use std::f32::consts::PI;
+
+use bevy::{prelude::*, ui::UiSystem};
+use bevy_xpbd_3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config,
+    plugins::{controls::InputAction, species::SelectedSpecies},
+    Birb,
+};
+
+const CONFIG_FILE: &str = "flight_params.ron";
+
+pub(crate) struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(config::load::<FlightParams>(CONFIG_FILE).unwrap_or_default())
+            .add_systems(Startup, setup_tuning_panel)
+            .add_systems(
+                PreUpdate,
+                (drag_sliders, tuning_buttons)
+                    .in_set(TuningSet)
+                    .after(UiSystem::Focus),
+            )
+            .add_systems(
+                Update,
+                (
+                    toggle_tuning_panel,
+                    update_slider_visuals,
+                    apply_flight_params,
+                ),
+            );
+    }
+}
+
+/// Edits the [`FlightParams`] from the panel. Runs before `Update`, so a change applies to the
+/// whole frame and a replay can apply it at the same point.
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+pub(crate) struct TuningSet;
+
+/// Everything that shapes how the birb flies. Edited live through the tuning panel.
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct FlightParams {
+    pub(crate) linear_damping: f32,
+    pub(crate) angular_damping: f32,
+    pub(crate) angular_acceleration: f32,
+    /// How hard a joint is pulled towards its target angle in [`ControlMode::TargetAngle`].
+    ///
+    /// [`ControlMode::TargetAngle`]: crate::plugins::control_mode::ControlMode::TargetAngle
+    pub(crate) target_stiffness: f32,
+    /// How much a joint's angular velocity is damped on its way to the target angle.
+    pub(crate) target_damping: f32,
+    /// Forward/up force of a joint while it is flapping down.
+    pub(crate) flap_force: f32,
+    /// Forward/up force of a joint while it is being pulled back up.
+    pub(crate) flap_force_recovery: f32,
+    /// Force applied at the joint itself while flapping down, which rolls the birb.
+    pub(crate) lift_force: f32,
+    pub(crate) lift_force_recovery: f32,
+    pub(crate) pitch_force: f32,
+    pub(crate) wind_strength: f32,
+    pub(crate) gravity: f32,
+    pub(crate) min_wing_angle: f32,
+    pub(crate) max_wing_angle: f32,
+    /// How much the lift follows the simulated wing cloth instead of the rigid wing, from 0 to 1.
+    pub(crate) cloth_lift: f32,
+}
+
+impl Default for FlightParams {
+    fn default() -> Self {
+        Self {
+            linear_damping: 0.16,
+            angular_damping: 1.6,
+            angular_acceleration: 20.0,
+            target_stiffness: 200.0,
+            target_damping: 25.0,
+            flap_force: 5.0,
+            flap_force_recovery: 1.0,
+            lift_force: 0.1,
+            lift_force_recovery: 0.001,
+            pitch_force: 0.35,
+            wind_strength: 0.01,
+            gravity: 9.81,
+            min_wing_angle: -0.15 * PI,
+            max_wing_angle: 0.15 * PI,
+            cloth_lift: 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum TuningParam {
+    LinearDamping,
+    AngularDamping,
+    AngularAcceleration,
+    TargetStiffness,
+    TargetDamping,
+    FlapForce,
+    FlapForceRecovery,
+    LiftForce,
+    LiftForceRecovery,
+    PitchForce,
+    WindStrength,
+    Gravity,
+    MinWingAngle,
+    MaxWingAngle,
+    ClothLift,
+}
+
+impl TuningParam {
+    const ALL: [TuningParam; 15] = [
+        TuningParam::LinearDamping,
+        TuningParam::AngularDamping,
+        TuningParam::AngularAcceleration,
+        TuningParam::TargetStiffness,
+        TuningParam::TargetDamping,
+        TuningParam::FlapForce,
+        TuningParam::FlapForceRecovery,
+        TuningParam::LiftForce,
+        TuningParam::LiftForceRecovery,
+        TuningParam::PitchForce,
+        TuningParam::WindStrength,
+        TuningParam::Gravity,
+        TuningParam::MinWingAngle,
+        TuningParam::MaxWingAngle,
+        TuningParam::ClothLift,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            TuningParam::LinearDamping => "Linear damping",
+            TuningParam::AngularDamping => "Angular damping",
+            TuningParam::AngularAcceleration => "Wing acceleration",
+            TuningParam::TargetStiffness => "Target stiffness",
+            TuningParam::TargetDamping => "Target damping",
+            TuningParam::FlapForce => "Flap force",
+            TuningParam::FlapForceRecovery => "Flap force (up)",
+            TuningParam::LiftForce => "Lift force",
+            TuningParam::LiftForceRecovery => "Lift force (up)",
+            TuningParam::PitchForce => "Pitch force",
+            TuningParam::WindStrength => "Wind strength",
+            TuningParam::Gravity => "Gravity",
+            TuningParam::MinWingAngle => "Min wing angle",
+            TuningParam::MaxWingAngle => "Max wing angle",
+            TuningParam::ClothLift => "Cloth lift",
+        }
+    }
+
+    fn range(self) -> (f32, f32) {
+        match self {
+            TuningParam::LinearDamping => (0.0, 2.0),
+            TuningParam::AngularDamping => (0.0, 10.0),
+            TuningParam::AngularAcceleration => (1.0, 100.0),
+            TuningParam::TargetStiffness => (10.0, 1000.0),
+            TuningParam::TargetDamping => (0.0, 100.0),
+            TuningParam::FlapForce => (0.0, 20.0),
+            TuningParam::FlapForceRecovery => (0.0, 5.0),
+            TuningParam::LiftForce => (0.0, 1.0),
+            TuningParam::LiftForceRecovery => (0.0, 0.05),
+            TuningParam::PitchForce => (0.0, 2.0),
+            TuningParam::WindStrength => (0.0, 0.1),
+            TuningParam::Gravity => (0.0, 30.0),
+            TuningParam::MinWingAngle => (-0.5 * PI, 0.0),
+            TuningParam::MaxWingAngle => (0.0, 0.5 * PI),
+            TuningParam::ClothLift => (0.0, 1.0),
+        }
+    }
+
+    fn get(self, params: &FlightParams) -> f32 {
+        match self {
+            TuningParam::LinearDamping => params.linear_damping,
+            TuningParam::AngularDamping => params.angular_damping,
+            TuningParam::AngularAcceleration => params.angular_acceleration,
+            TuningParam::TargetStiffness => params.target_stiffness,
+            TuningParam::TargetDamping => params.target_damping,
+            TuningParam::FlapForce => params.flap_force,
+            TuningParam::FlapForceRecovery => params.flap_force_recovery,
+            TuningParam::LiftForce => params.lift_force,
+            TuningParam::LiftForceRecovery => params.lift_force_recovery,
+            TuningParam::PitchForce => params.pitch_force,
+            TuningParam::WindStrength => params.wind_strength,
+            TuningParam::Gravity => params.gravity,
+            TuningParam::MinWingAngle => params.min_wing_angle,
+            TuningParam::MaxWingAngle => params.max_wing_angle,
+            TuningParam::ClothLift => params.cloth_lift,
+        }
+    }
+
+    fn get_mut(self, params: &mut FlightParams) -> &mut f32 {
+        match self {
+            TuningParam::LinearDamping => &mut params.linear_damping,
+            TuningParam::AngularDamping => &mut params.angular_damping,
+            TuningParam::AngularAcceleration => &mut params.angular_acceleration,
+            TuningParam::TargetStiffness => &mut params.target_stiffness,
+            TuningParam::TargetDamping => &mut params.target_damping,
+            TuningParam::FlapForce => &mut params.flap_force,
+            TuningParam::FlapForceRecovery => &mut params.flap_force_recovery,
+            TuningParam::LiftForce => &mut params.lift_force,
+            TuningParam::LiftForceRecovery => &mut params.lift_force_recovery,
+            TuningParam::PitchForce => &mut params.pitch_force,
+            TuningParam::WindStrength => &mut params.wind_strength,
+            TuningParam::Gravity => &mut params.gravity,
+            TuningParam::MinWingAngle => &mut params.min_wing_angle,
+            TuningParam::MaxWingAngle => &mut params.max_wing_angle,
+            TuningParam::ClothLift => &mut params.cloth_lift,
+        }
+    }
+}
+
+#[derive(Component)]
+struct TuningPanel;
+
+/// The clickable track of a slider.
+#[derive(Component)]
+struct TuningSlider(TuningParam);
+
+#[derive(Component)]
+struct TuningSliderFill(TuningParam);
+
+#[derive(Component)]
+struct TuningValueText(TuningParam);
+
+#[derive(Component)]
+enum TuningButton {
+    Save,
+    Reset,
+}
+
+const SLIDER_WIDTH: f32 = 220.0;
+const FONT_SIZE: f32 = 18.0;
+
+fn setup_tuning_panel(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+    let text_style = TextStyle {
+        font,
+        font_size: FONT_SIZE,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(10.0),
+                    top: Val::Px(10.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.0),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.75).into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            TuningPanel,
+        ))
+        .with_children(|panel| {
+            for param in TuningParam::ALL {
+                panel.spawn((
+                    TextBundle::from_section(param.label(), text_style.clone()),
+                    TuningValueText(param),
+                ));
+                panel
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(SLIDER_WIDTH),
+                                height: Val::Px(10.0),
+                                margin: UiRect::bottom(Val::Px(4.0)),
+                                ..default()
+                            },
+                            background_color: Color::DARK_GRAY.into(),
+                            ..default()
+                        },
+                        TuningSlider(param),
+                    ))
+                    .with_children(|track| {
+                        track.spawn((
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Percent(0.0),
+                                    height: Val::Percent(100.0),
+                                    ..default()
+                                },
+                                background_color: Color::GOLD.into(),
+                                ..default()
+                            },
+                            TuningSliderFill(param),
+                        ));
+                    });
+            }
+            panel
+                .spawn(NodeBundle {
+                    style: Style {
+                        column_gap: Val::Px(8.0),
+                        margin: UiRect::top(Val::Px(6.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|buttons| {
+                    for (button, label) in
+                        [(TuningButton::Save, "Save"), (TuningButton::Reset, "Reset")]
+                    {
+                        buttons
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                                        ..default()
+                                    },
+                                    background_color: Color::DARK_GRAY.into(),
+                                    ..default()
+                                },
+                                button,
+                            ))
+                            .with_children(|b| {
+                                b.spawn(TextBundle::from_section(label, text_style.clone()));
+                            });
+                    }
+                });
+        });
+}
+
+fn toggle_tuning_panel(
+    actions: Res<Input<InputAction>>,
+    mut panel: Query<&mut Visibility, With<TuningPanel>>,
+) {
+    if actions.just_pressed(InputAction::ToggleTuning) {
+        for mut visibility in &mut panel {
+            *visibility = match *visibility {
+                Visibility::Hidden => Visibility::Visible,
+                Visibility::Visible | Visibility::Inherited => Visibility::Hidden,
+            };
+        }
+    }
+}
+
+fn drag_sliders(
+    windows: Query<&Window>,
+    sliders: Query<(&Interaction, &Node, &GlobalTransform, &TuningSlider)>,
+    mut params: ResMut<FlightParams>,
+) {
+    let Some(cursor) = windows.iter().next().and_then(|w| w.cursor_position()) else {
+        return;
+    };
+    for (interaction, node, transform, slider) in &sliders {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let rect = node.logical_rect(transform);
+        let t = ((cursor.x - rect.min.x) / rect.width()).clamp(0.0, 1.0);
+        let (min, max) = slider.0.range();
+        let value = min + t * (max - min);
+        let field = slider.0.get_mut(params.bypass_change_detection());
+        if *field != value {
+            *field = value;
+            params.set_changed();
+        }
+    }
+}
+
+fn tuning_buttons(
+    buttons: Query<(&Interaction, &TuningButton), Changed<Interaction>>,
+    mut params: ResMut<FlightParams>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button {
+            TuningButton::Save => config::save(CONFIG_FILE, &*params),
+            TuningButton::Reset => *params = FlightParams::default(),
+        }
+    }
+}
+
+fn update_slider_visuals(
+    params: Res<FlightParams>,
+    mut fills: Query<(&mut Style, &TuningSliderFill)>,
+    mut texts: Query<(&mut Text, &TuningValueText)>,
+) {
+    if !params.is_changed() {
+        return;
+    }
+    for (mut style, fill) in &mut fills {
+        let (min, max) = fill.0.range();
+        let t = (fill.0.get(&params) - min) / (max - min);
+        style.width = Val::Percent(t.clamp(0.0, 1.0) * 100.0);
+    }
+    for (mut text, value_text) in &mut texts {
+        text.sections[0].value =
+            format!("{}: {:.3}", value_text.0.label(), value_text.0.get(&params));
+    }
+}
+
+fn apply_flight_params(
+    params: Res<FlightParams>,
+    species: Res<SelectedSpecies>,
+    mut gravity: ResMut<Gravity>,
+    mut birb: Query<(&mut LinearDamping, &mut AngularDamping), With<Birb>>,
+) {
+    if !params.is_changed() && !species.is_changed() {
+        return;
+    }
+    gravity.0 = Vec3::NEG_Y * params.gravity;
+    let stats = species.0.stats();
+    for (mut linear, mut angular) in &mut birb {
+        linear.0 = params.linear_damping * stats.linear_damping;
+        angular.0 = params.angular_damping * stats.angular_damping;
+    }
+}
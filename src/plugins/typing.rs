@@ -0,0 +1,470 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config,
+    plugins::{
+        controls::{ControlsSet, InputAction, PendingActions},
+        replay::PlaybackSet,
+        score::ScoreState,
+    },
+    AppState,
+};
+
+const CONFIG_FILE: &str = "typing.ron";
+
+/// Typing trainer: home-row words scroll by and every correctly typed letter flaps the joint
+/// under that finger. While it is on, the wing keys only flap through typing.
+pub(crate) struct TypingPlugin;
+
+impl Plugin for TypingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(config::load::<TypingSettings>(CONFIG_FILE).unwrap_or_default())
+            .init_resource::<TypingState>()
+            .add_systems(Startup, setup_typing_ui)
+            .add_systems(
+                PreUpdate,
+                typing_flaps
+                    .in_set(ControlsSet::Override)
+                    .before(PlaybackSet),
+            )
+            .add_systems(
+                Update,
+                (
+                    toggle_typing,
+                    typing_buttons,
+                    type_letters.run_if(in_state(AppState::InGame)),
+                    scroll_words.run_if(in_state(AppState::InGame)),
+                    update_typing_ui,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub(crate) enum WordList {
+    /// Finger drills like `fjfj` and `asdf`.
+    Drills,
+    /// Real words that only use home-row letters.
+    #[default]
+    Words,
+    /// Drills and words, plus words ending in `;`.
+    Mixed,
+}
+
+impl WordList {
+    const DRILLS: &'static [&'static str] = &[
+        "fj", "dk", "sl", "a;", "fjfj", "dkdk", "slsl", "a;a;", "asdf", "jkl;", "fdsa", ";lkj",
+        "fjdk", "sla;", "adsf", "jlk;", "ffjj", "ddkk", "ssll", "aa;;",
+    ];
+    const WORDS: &'static [&'static str] = &[
+        "a", "ad", "add", "adds", "all", "alas", "as", "ask", "asks", "dad", "dads", "fad", "fads",
+        "fall", "falls", "flask", "flasks", "lad", "lads", "lass", "sad", "salad", "salads",
+        "alfalfa", "jak", "flak", "skald", "sass", "ska",
+    ];
+    const PUNCTUATED: &'static [&'static str] =
+        &["add;", "ask;", "alas;", "fall;", "sad;", "salad;"];
+
+    fn words(self) -> Vec<&'static str> {
+        match self {
+            WordList::Drills => Self::DRILLS.to_vec(),
+            WordList::Words => Self::WORDS.to_vec(),
+            WordList::Mixed => [Self::DRILLS, Self::WORDS, Self::PUNCTUATED].concat(),
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            WordList::Drills => WordList::Words,
+            WordList::Words => WordList::Mixed,
+            WordList::Mixed => WordList::Drills,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub(crate) enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    /// How fast the words scroll, in pixels per second.
+    fn speed(self) -> f32 {
+        match self {
+            Difficulty::Easy => 40.0,
+            Difficulty::Normal => 70.0,
+            Difficulty::Hard => 110.0,
+        }
+    }
+
+    fn max_word_len(self) -> usize {
+        match self {
+            Difficulty::Easy => 4,
+            Difficulty::Normal => 6,
+            Difficulty::Hard => usize::MAX,
+        }
+    }
+
+    /// Score for every letter of a completed word.
+    fn letter_bonus(self) -> f32 {
+        match self {
+            Difficulty::Easy => 10.0,
+            Difficulty::Normal => 20.0,
+            Difficulty::Hard => 40.0,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+}
+
+#[derive(Resource, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct TypingSettings {
+    pub(crate) word_list: WordList,
+    pub(crate) difficulty: Difficulty,
+}
+
+struct ScrollingWord {
+    entity: Entity,
+    text: &'static str,
+    typed: usize,
+    /// Distance of the word from the left edge of the strip.
+    x: f32,
+}
+
+#[derive(Resource, Default)]
+pub(crate) struct TypingState {
+    pub(crate) active: bool,
+    words: VecDeque<ScrollingWord>,
+    /// Seconds each joint keeps flapping, in `BirbState::angles` order.
+    flaps: [f32; 8],
+}
+
+const STRIP_WIDTH: f32 = 600.0;
+const STRIP_HEIGHT: f32 = 48.0;
+const FONT_SIZE: f32 = 32.0;
+/// Advance of one character in the monospace font.
+const CHAR_WIDTH: f32 = FONT_SIZE * 0.6;
+const WORD_GAP: f32 = 2.0 * CHAR_WIDTH;
+/// How long a typed letter holds its joint down.
+const FLAP_SECS: f32 = 0.25;
+const TYPED_COLOR: Color = Color::GOLD;
+
+/// The wing joint under the finger that types `letter`.
+fn letter_joint(letter: char) -> Option<InputAction> {
+    match letter {
+        'a' => Some(InputAction::LeftWingJoint(3)),
+        's' => Some(InputAction::LeftWingJoint(2)),
+        'd' => Some(InputAction::LeftWingJoint(1)),
+        'f' => Some(InputAction::LeftWingJoint(0)),
+        'j' => Some(InputAction::RightWingJoint(0)),
+        'k' => Some(InputAction::RightWingJoint(1)),
+        'l' => Some(InputAction::RightWingJoint(2)),
+        ';' => Some(InputAction::RightWingJoint(3)),
+        _ => None,
+    }
+}
+
+#[derive(Component)]
+struct TypingUi;
+
+#[derive(Component)]
+struct WordStrip;
+
+#[derive(Component)]
+struct WordText;
+
+#[derive(Component)]
+enum TypingButton {
+    WordList,
+    Difficulty,
+}
+
+#[derive(Resource)]
+struct TypingFont(Handle<Font>);
+
+fn setup_typing_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+    commands.insert_resource(TypingFont(font.clone()));
+    let button_style = TextStyle {
+        font,
+        font_size: 18.0,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(10.0),
+                    width: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            TypingUi,
+        ))
+        .with_children(|root| {
+            root.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Px(STRIP_WIDTH),
+                        height: Val::Px(STRIP_HEIGHT),
+                        overflow: Overflow::clip(),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+                    ..default()
+                },
+                WordStrip,
+            ));
+            root.spawn(NodeBundle {
+                style: Style {
+                    column_gap: Val::Px(8.0),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|buttons| {
+                for button in [TypingButton::WordList, TypingButton::Difficulty] {
+                    buttons
+                        .spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    padding: UiRect::axes(Val::Px(10.0), Val::Px(2.0)),
+                                    ..default()
+                                },
+                                background_color: Color::DARK_GRAY.into(),
+                                ..default()
+                            },
+                            button,
+                        ))
+                        .with_children(|b| {
+                            b.spawn(TextBundle::from_section("", button_style.clone()));
+                        });
+                }
+            });
+        });
+}
+
+fn toggle_typing(
+    mut commands: Commands,
+    actions: Res<Input<InputAction>>,
+    mut state: ResMut<TypingState>,
+    mut ui: Query<&mut Visibility, With<TypingUi>>,
+) {
+    if !actions.just_pressed(InputAction::ToggleTyping) {
+        return;
+    }
+    state.active = !state.active;
+    for word in state.words.drain(..) {
+        commands.entity(word.entity).despawn_recursive();
+    }
+    state.flaps = [0.0; 8];
+    for mut visibility in &mut ui {
+        *visibility = if state.active {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn typing_buttons(
+    buttons: Query<(&Interaction, &TypingButton), Changed<Interaction>>,
+    mut settings: ResMut<TypingSettings>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button {
+            TypingButton::WordList => settings.word_list = settings.word_list.next(),
+            TypingButton::Difficulty => settings.difficulty = settings.difficulty.next(),
+        }
+        config::save(CONFIG_FILE, &*settings);
+    }
+}
+
+fn type_letters(
+    mut commands: Commands,
+    mut characters: EventReader<ReceivedCharacter>,
+    settings: Res<TypingSettings>,
+    mut state: ResMut<TypingState>,
+    mut score: ResMut<ScoreState>,
+) {
+    if !state.active {
+        characters.clear();
+        return;
+    }
+    for event in characters.read() {
+        let letter = event.char.to_ascii_lowercase();
+        if letter.is_whitespace() || letter.is_control() {
+            continue;
+        }
+        let Some(word) = state.words.front_mut() else {
+            continue;
+        };
+        if !word.text[word.typed..].starts_with(letter) {
+            score.typing.mistakes += 1;
+            continue;
+        }
+        word.typed += letter.len_utf8();
+        score.typing.typed += 1;
+        if word.typed == word.text.len() {
+            score.bonus += word.text.len() as f32 * settings.difficulty.letter_bonus();
+            commands.entity(word.entity).despawn_recursive();
+            state.words.pop_front();
+        }
+        if let Some(index) = letter_joint(letter).and_then(InputAction::joint_index) {
+            state.flaps[index] = FLAP_SECS;
+        }
+    }
+}
+
+fn scroll_words(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<TypingSettings>,
+    font: Res<TypingFont>,
+    strips: Query<Entity, With<WordStrip>>,
+    mut state: ResMut<TypingState>,
+    mut score: ResMut<ScoreState>,
+) {
+    if !state.active {
+        return;
+    }
+    let dt = time.delta_seconds();
+    score.typing.secs += dt;
+    for flap in &mut state.flaps {
+        *flap = (*flap - dt).max(0.0);
+    }
+
+    for word in &mut state.words {
+        word.x -= settings.difficulty.speed() * dt;
+    }
+    // Words that reach the left edge unfinished count as mistakes.
+    while state.words.front().is_some_and(|word| word.x < 0.0) {
+        let word = state.words.pop_front().unwrap();
+        score.typing.mistakes += word.text[word.typed..].chars().count() as u32;
+        commands.entity(word.entity).despawn_recursive();
+    }
+
+    let room_for_next = state.words.back().is_none_or(|word| {
+        word.x + word.text.chars().count() as f32 * CHAR_WIDTH + WORD_GAP <= STRIP_WIDTH
+    });
+    let Ok(strip) = strips.get_single() else {
+        return;
+    };
+    if room_for_next {
+        let candidates: Vec<_> = settings
+            .word_list
+            .words()
+            .into_iter()
+            .filter(|word| word.chars().count() <= settings.difficulty.max_word_len())
+            .collect();
+        let Some(&text) = candidates.choose(&mut rand::thread_rng()) else {
+            return;
+        };
+        let style = TextStyle {
+            font: font.0.clone(),
+            font_size: FONT_SIZE,
+            color: Color::WHITE,
+        };
+        let entity = commands
+            .spawn((
+                TextBundle {
+                    text: Text::from_sections([
+                        TextSection::from_style(TextStyle {
+                            color: TYPED_COLOR,
+                            ..style.clone()
+                        }),
+                        TextSection::new(text, style),
+                    ]),
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(STRIP_WIDTH),
+                        top: Val::Px((STRIP_HEIGHT - FONT_SIZE) / 2.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                WordText,
+            ))
+            .id();
+        commands.entity(strip).add_child(entity);
+        state.words.push_back(ScrollingWord {
+            entity,
+            text,
+            typed: 0,
+            x: STRIP_WIDTH,
+        });
+    }
+}
+
+fn update_typing_ui(
+    state: Res<TypingState>,
+    settings: Res<TypingSettings>,
+    mut words: Query<(&mut Style, &mut Text), With<WordText>>,
+    buttons: Query<(&TypingButton, &Children)>,
+    mut button_texts: Query<&mut Text, Without<WordText>>,
+) {
+    for word in &state.words {
+        let Ok((mut style, mut text)) = words.get_mut(word.entity) else {
+            continue;
+        };
+        style.left = Val::Px(word.x);
+        let (typed, rest) = word.text.split_at(word.typed);
+        if text.sections[0].value != typed {
+            text.sections[0].value = typed.to_string();
+            text.sections[1].value = rest.to_string();
+        }
+    }
+
+    if !settings.is_changed() {
+        return;
+    }
+    for (button, children) in &buttons {
+        let label = match button {
+            TypingButton::WordList => format!("Words: {:?}", settings.word_list),
+            TypingButton::Difficulty => format!("Difficulty: {:?}", settings.difficulty),
+        };
+        for &child in children {
+            if let Ok(mut text) = button_texts.get_mut(child) {
+                text.sections[0].value = label.clone();
+            }
+        }
+    }
+}
+
+/// While typing, the wing joints only move for freshly typed letters.
+fn typing_flaps(state: Res<TypingState>, mut pending: ResMut<PendingActions>) {
+    if !state.active {
+        return;
+    }
+    for (index, flap) in state.flaps.iter().enumerate() {
+        let action = InputAction::wing_joint(index);
+        pending.release(action);
+        if *flap > 0.0 {
+            pending.set_analog(action, 1.0);
+        }
+    }
+}
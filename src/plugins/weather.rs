@@ -0,0 +1,329 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config,
+    plugins::{
+        replay::{RngStream, RunSeed},
+        score::ScoreState,
+        wind::WindField,
+    },
+    AppState, Birb, TerrainState,
+};
+
+const CONFIG_FILE: &str = "weather.ron";
+
+pub(crate) struct WeatherPlugin;
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        let mode = config::load::<WeatherMode>(CONFIG_FILE).unwrap_or_default();
+        let rng = app.world.resource::<RunSeed>().rng(RngStream::Weather);
+        app.insert_resource(WeatherState::new(mode, rng))
+            .add_systems(Startup, rain_setup)
+            .add_systems(Update, (update_weather, apply_weather_fog).chain())
+            .add_systems(
+                Update,
+                (spawn_rain, move_rain).run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub(crate) enum Weather {
+    Clear,
+    Overcast,
+    Rain,
+    Storm,
+}
+
+impl Weather {
+    const ALL: [Weather; 4] = [
+        Weather::Clear,
+        Weather::Overcast,
+        Weather::Rain,
+        Weather::Storm,
+    ];
+
+    fn effects(self) -> WeatherEffects {
+        match self {
+            Weather::Clear => WeatherEffects {
+                wind: 1.0,
+                turbulence: 1.0,
+                visibility: 4000.0,
+                light: 1.0,
+                fog_color: Color::rgb(0.7, 0.8, 0.95),
+                rain: 0.0,
+                score_multiplier: 1.0,
+            },
+            Weather::Overcast => WeatherEffects {
+                wind: 1.5,
+                turbulence: 1.3,
+                visibility: 2000.0,
+                light: 0.5,
+                fog_color: Color::rgb(0.6, 0.62, 0.66),
+                rain: 0.0,
+                score_multiplier: 1.0,
+            },
+            Weather::Rain => WeatherEffects {
+                wind: 2.0,
+                turbulence: 1.8,
+                visibility: 900.0,
+                light: 0.3,
+                fog_color: Color::rgb(0.45, 0.48, 0.52),
+                rain: 0.6,
+                score_multiplier: 1.25,
+            },
+            Weather::Storm => WeatherEffects {
+                wind: 3.5,
+                turbulence: 3.0,
+                visibility: 400.0,
+                light: 0.15,
+                fog_color: Color::rgb(0.25, 0.27, 0.3),
+                rain: 1.0,
+                score_multiplier: 2.0,
+            },
+        }
+    }
+}
+
+/// How a run picks its weather.
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub(crate) enum WeatherMode {
+    /// Drift from one weather to the next over time.
+    #[default]
+    Cycle,
+    /// Pick one weather at random and keep it for the whole run.
+    Random,
+    Fixed(Weather),
+}
+
+/// Everything the current weather changes, blended while the weather is transitioning.
+#[derive(Clone, Copy)]
+pub(crate) struct WeatherEffects {
+    pub(crate) wind: f32,
+    pub(crate) turbulence: f32,
+    /// Distance in world units at which the fog swallows everything.
+    pub(crate) visibility: f32,
+    /// Multiplier for sun and ambient light.
+    pub(crate) light: f32,
+    pub(crate) fog_color: Color,
+    /// Rain intensity between 0 and 1.
+    pub(crate) rain: f32,
+    pub(crate) score_multiplier: f32,
+}
+
+impl WeatherEffects {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+        Self {
+            wind: mix(self.wind, other.wind),
+            turbulence: mix(self.turbulence, other.turbulence),
+            visibility: mix(self.visibility, other.visibility),
+            light: mix(self.light, other.light),
+            fog_color: Color::rgb(
+                mix(self.fog_color.r(), other.fog_color.r()),
+                mix(self.fog_color.g(), other.fog_color.g()),
+                mix(self.fog_color.b(), other.fog_color.b()),
+            ),
+            rain: mix(self.rain, other.rain),
+            score_multiplier: mix(self.score_multiplier, other.score_multiplier),
+        }
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct WeatherState {
+    pub(crate) current: Weather,
+    previous: Weather,
+    pub(crate) mode: WeatherMode,
+    changed_at: f64,
+    next_change: f64,
+    pub(crate) effects: WeatherEffects,
+    rng: StdRng,
+}
+
+/// Seconds it takes to blend from one weather into the next.
+const TRANSITION_SECS: f64 = 20.0;
+const WEATHER_DURATION: (f64, f64) = (60.0, 180.0);
+/// Fraction of the terrain view radius at which the fog becomes opaque.
+const FOG_END_FRACTION: f32 = 0.9;
+/// Fraction of the fog end distance at which the fog starts.
+const FOG_START_FRACTION: f32 = 0.2;
+
+impl WeatherState {
+    pub(crate) fn new(mode: WeatherMode, mut rng: StdRng) -> Self {
+        let current = match mode {
+            WeatherMode::Cycle => Weather::Clear,
+            WeatherMode::Random => Weather::ALL[rng.gen_range(0..Weather::ALL.len())],
+            WeatherMode::Fixed(weather) => weather,
+        };
+        Self {
+            current,
+            previous: current,
+            mode,
+            changed_at: 0.0,
+            next_change: WEATHER_DURATION.0,
+            effects: current.effects(),
+            rng,
+        }
+    }
+}
+
+fn update_weather(
+    time: Res<Time>,
+    mut weather: ResMut<WeatherState>,
+    mut wind: ResMut<WindField>,
+    mut score: ResMut<ScoreState>,
+) {
+    let now = time.elapsed_seconds_f64();
+    if matches!(weather.mode, WeatherMode::Cycle) && now >= weather.next_change {
+        let next = loop {
+            let candidate = Weather::ALL[weather.rng.gen_range(0..Weather::ALL.len())];
+            if candidate != weather.current {
+                break candidate;
+            }
+        };
+        info!("Weather changing from {:?} to {:?}", weather.current, next);
+        weather.previous = weather.current;
+        weather.current = next;
+        weather.changed_at = now;
+        weather.next_change = now
+            + weather
+                .rng
+                .gen_range(WEATHER_DURATION.0..WEATHER_DURATION.1);
+    }
+
+    let t = ((now - weather.changed_at) / TRANSITION_SECS).clamp(0.0, 1.0) as f32;
+    weather.effects = weather
+        .previous
+        .effects()
+        .lerp(weather.current.effects(), t);
+
+    wind.strength_multiplier = weather.effects.wind;
+    wind.turbulence_multiplier = weather.effects.turbulence;
+    score.multiplier = weather.effects.score_multiplier;
+}
+
+/// Thickens the fog as the weather gets worse. Its colour follows the sky, see
+/// [`DayNightPlugin`](crate::plugins::day_night::DayNightPlugin).
+///
+/// Even in clear weather the fog is opaque before the edge of the loaded terrain, so chunks
+/// streaming in and out are never visible.
+fn apply_weather_fog(
+    mut commands: Commands,
+    weather: Res<WeatherState>,
+    terrain_state: Res<TerrainState>,
+    mut cameras: Query<(Entity, Option<&mut FogSettings>), With<Camera3d>>,
+) {
+    let effects = weather.effects;
+    let end = effects
+        .visibility
+        .min(terrain_state.view_radius * FOG_END_FRACTION);
+    for (entity, fog) in &mut cameras {
+        let falloff = FogFalloff::Linear {
+            start: end * FOG_START_FRACTION,
+            end,
+        };
+        match fog {
+            Some(mut fog) => fog.falloff = falloff,
+            None => {
+                commands.entity(entity).insert(FogSettings {
+                    color: effects.fog_color,
+                    falloff,
+                    ..default()
+                });
+            }
+        }
+    }
+}
+
+#[derive(Resource)]
+struct RainAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+#[derive(Component)]
+struct RainDrop;
+
+/// Drops alive around the birb at full rain intensity.
+const MAX_RAIN_DROPS: f32 = 400.0;
+const MAX_SPAWN_PER_FRAME: usize = 20;
+const RAIN_AREA: f32 = 30.0;
+const RAIN_HEIGHT: (f32, f32) = (5.0, 30.0);
+const RAIN_SPEED: f32 = 25.0;
+/// How strongly the wind blows the rain sideways.
+const RAIN_WIND_DRIFT: f32 = 3.0;
+
+fn rain_setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(RainAssets {
+        mesh: meshes.add(Mesh::from(shape::Box::new(0.02, 0.6, 0.02))),
+        material: materials.add(StandardMaterial {
+            base_color: Color::rgba(0.7, 0.75, 0.85, 0.5),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+fn spawn_rain(
+    mut commands: Commands,
+    assets: Res<RainAssets>,
+    weather: Res<WeatherState>,
+    drops: Query<(), With<RainDrop>>,
+    birb: Query<&GlobalTransform, With<Birb>>,
+) {
+    let Some(birb) = birb.iter().next() else {
+        return;
+    };
+    let wanted = (MAX_RAIN_DROPS * weather.effects.rain) as usize;
+    let missing = wanted.saturating_sub(drops.iter().count());
+
+    let mut rng = rand::thread_rng();
+    for _ in 0..missing.min(MAX_SPAWN_PER_FRAME) {
+        let offset = Vec3::new(
+            rng.gen_range(-RAIN_AREA..RAIN_AREA),
+            rng.gen_range(RAIN_HEIGHT.0..RAIN_HEIGHT.1),
+            rng.gen_range(-RAIN_AREA..RAIN_AREA),
+        );
+        commands.spawn((
+            PbrBundle {
+                mesh: assets.mesh.clone(),
+                material: assets.material.clone(),
+                transform: Transform::from_translation(birb.translation() + offset),
+                ..default()
+            },
+            RainDrop,
+        ));
+    }
+}
+
+fn move_rain(
+    mut commands: Commands,
+    time: Res<Time>,
+    wind: Res<WindField>,
+    mut drops: Query<(Entity, &mut Transform), With<RainDrop>>,
+    birb: Query<&GlobalTransform, With<Birb>>,
+) {
+    let Some(birb_pos) = birb.iter().next().map(|b| b.translation()) else {
+        return;
+    };
+    for (entity, mut transform) in &mut drops {
+        let velocity =
+            Vec3::NEG_Y * RAIN_SPEED + wind.sample(transform.translation) * RAIN_WIND_DRIFT;
+        transform.translation += velocity * time.delta_seconds();
+        transform.rotation = Quat::from_rotation_arc(Vec3::NEG_Y, velocity.normalize());
+
+        let offset = transform.translation - birb_pos;
+        if offset.y < -RAIN_HEIGHT.1 || offset.x.abs() > RAIN_AREA || offset.z.abs() > RAIN_AREA {
+            commands.entity(entity).despawn();
+        }
+    }
+}
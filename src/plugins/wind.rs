@@ -0,0 +1,150 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use noise::{NoiseFn, Perlin};
+use rand::{rngs::StdRng, Rng};
+
+use crate::plugins::replay::{RngStream, RunSeed};
+
+pub(crate) struct WindPlugin;
+
+impl Plugin for WindPlugin {
+    fn build(&self, app: &mut App) {
+        let seed = *app.world.resource::<RunSeed>();
+        app.insert_resource(WindField::new(seed.0 as u32, seed.rng(RngStream::Wind)))
+            .add_systems(PreUpdate, update_wind_field);
+    }
+}
+
+/// A short burst of extra wind on top of the prevailing wind.
+struct Gust {
+    start: f64,
+    duration: f64,
+    direction: Vec3,
+    strength: f32,
+}
+
+impl Gust {
+    /// Ramps smoothly from 0 to 1 and back over the lifetime of the gust.
+    fn envelope(&self, time: f64) -> f32 {
+        let progress = ((time - self.start) / self.duration).clamp(0.0, 1.0) as f32;
+        (progress * PI).sin()
+    }
+}
+
+/// The wind blowing over the whole world.
+///
+/// The prevailing wind slowly drifts in direction and strength, gusts are scheduled on top of
+/// it and everything gets stronger with altitude. All noise is sampled from one cached [`Perlin`].
+#[derive(Resource)]
+pub(crate) struct WindField {
+    perlin: Perlin,
+    time: f64,
+    /// Horizontal direction the prevailing wind blows towards.
+    pub(crate) direction: Vec3,
+    pub(crate) strength: f32,
+    /// Scales the prevailing wind and gusts, set by the weather.
+    pub(crate) strength_multiplier: f32,
+    /// Scales the local turbulence, set by the weather.
+    pub(crate) turbulence_multiplier: f32,
+    gusts: Vec<Gust>,
+    next_gust: f64,
+    rng: StdRng,
+}
+
+const BASE_STRENGTH: f32 = 1.0;
+/// How much the prevailing strength wanders around [`BASE_STRENGTH`].
+const STRENGTH_VARIATION: f32 = 0.5;
+/// Altitude at which the wind is twice as strong as on the ground.
+const SHEAR_HEIGHT: f32 = 100.0;
+const GUST_INTERVAL: (f64, f64) = (5.0, 20.0);
+const GUST_DURATION: (f64, f64) = (1.5, 5.0);
+const GUST_STRENGTH: (f32, f32) = (1.0, 3.0);
+
+impl WindField {
+    pub(crate) fn new(seed: u32, rng: StdRng) -> Self {
+        Self {
+            perlin: Perlin::new(seed),
+            time: 0.0,
+            direction: Vec3::X,
+            strength: BASE_STRENGTH,
+            strength_multiplier: 1.0,
+            turbulence_multiplier: 1.0,
+            gusts: Vec::new(),
+            next_gust: GUST_INTERVAL.0,
+            rng,
+        }
+    }
+
+    pub(crate) fn noise(&self, point: [f64; 4]) -> f32 {
+        self.perlin.get(point) as f32
+    }
+
+    pub(crate) fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Combined strength of all currently active gusts.
+    pub(crate) fn gust_strength(&self) -> f32 {
+        self.gusts
+            .iter()
+            .map(|g| g.strength * g.envelope(self.time))
+            .sum()
+    }
+
+    /// Scales the wind up with altitude, so flying high is rougher than skimming the ground.
+    fn shear(altitude: f32) -> f32 {
+        1.0 + altitude.max(0.0) / SHEAR_HEIGHT
+    }
+
+    /// Wind vector at `position`: prevailing wind plus gusts plus local turbulence.
+    pub(crate) fn sample(&self, position: Vec3) -> Vec3 {
+        let p = position.as_dvec3();
+        let turbulence = Vec3::new(
+            self.noise([p.x, p.y, p.z, self.time]),
+            self.noise([p.x + 100.0, p.y, p.z, self.time]),
+            self.noise([p.x + 200.0, p.y, p.z, self.time]),
+        );
+        let gusts: Vec3 = self
+            .gusts
+            .iter()
+            .map(|g| g.direction * g.strength * g.envelope(self.time))
+            .sum();
+        (self.direction * self.strength + gusts)
+            * Self::shear(position.y)
+            * self.strength_multiplier
+            + turbulence * self.turbulence_multiplier
+    }
+
+    /// How strongly the wing joints should be shaken around right now.
+    pub(crate) fn turbulence_intensity(&self) -> f32 {
+        (0.02 + self.noise([self.time, 0.0, 0.0, 0.0]) * 0.12 + self.gust_strength() * 0.03)
+            * self.turbulence_multiplier
+    }
+
+    fn update(&mut self, time: f64) {
+        self.time = time;
+
+        let heading = self.noise([time * 0.01, 0.5, 0.0, 0.0]) * PI;
+        self.direction = Quat::from_rotation_y(heading) * Vec3::X;
+        self.strength =
+            BASE_STRENGTH * (1.0 + self.noise([time * 0.02, 1.5, 0.0, 0.0]) * STRENGTH_VARIATION);
+
+        self.gusts.retain(|g| g.start + g.duration > time);
+        if time >= self.next_gust {
+            let rng = &mut self.rng;
+            let spread = rng.gen_range(-0.25 * PI..0.25 * PI);
+            self.gusts.push(Gust {
+                start: time,
+                duration: rng.gen_range(GUST_DURATION.0..GUST_DURATION.1),
+                direction: Quat::from_rotation_y(spread) * self.direction,
+                strength: rng.gen_range(GUST_STRENGTH.0..GUST_STRENGTH.1),
+            });
+            self.next_gust = time + rng.gen_range(GUST_INTERVAL.0..GUST_INTERVAL.1);
+        }
+    }
+}
+
+fn update_wind_field(time: Res<Time>, mut wind: ResMut<WindField>) {
+    wind.update(time.elapsed_seconds_f64());
+}
@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{plugins::wind::WindField, AppState, Birb};
+
+/// Draws short streaks drifting with the wind around the birb so players can see what is
+/// pushing them around.
+pub(crate) struct WindStreaksPlugin;
+
+impl Plugin for WindStreaksPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, wind_streaks_setup).add_systems(
+            Update,
+            (spawn_wind_streaks, move_wind_streaks).run_if(in_state(AppState::InGame)),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct WindStreakAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+#[derive(Component)]
+struct WindStreak {
+    age: f32,
+    lifetime: f32,
+}
+
+/// Streaks alive around the birb in calm air.
+const CALM_STREAKS: f32 = 40.0;
+/// Extra streaks per unit of gust strength.
+const GUST_STREAKS: f32 = 40.0;
+const MAX_SPAWN_PER_FRAME: usize = 8;
+const SPAWN_RADIUS: (f32, f32) = (4.0, 40.0);
+/// World units per second a streak travels per unit of wind.
+const STREAK_SPEED: f32 = 6.0;
+const STREAK_LIFETIME: (f32, f32) = (0.8, 2.0);
+
+fn wind_streaks_setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(WindStreakAssets {
+        mesh: meshes.add(Mesh::from(shape::Box::new(0.03, 0.03, 1.0))),
+        material: materials.add(StandardMaterial {
+            base_color: Color::rgba(1.0, 1.0, 1.0, 0.35),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+fn spawn_wind_streaks(
+    mut commands: Commands,
+    assets: Res<WindStreakAssets>,
+    wind: Res<WindField>,
+    streaks: Query<(), With<WindStreak>>,
+    birb: Query<&GlobalTransform, With<Birb>>,
+) {
+    let Some(birb) = birb.iter().next() else {
+        return;
+    };
+    let wanted = (CALM_STREAKS + GUST_STREAKS * wind.gust_strength()) as usize;
+    let missing = wanted.saturating_sub(streaks.iter().count());
+
+    let mut rng = rand::thread_rng();
+    for _ in 0..missing.min(MAX_SPAWN_PER_FRAME) {
+        let offset = Vec3::new(
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-0.5..0.5),
+            rng.gen_range(-1.0..1.0),
+        )
+        .normalize_or_zero()
+            * rng.gen_range(SPAWN_RADIUS.0..SPAWN_RADIUS.1);
+        commands.spawn((
+            PbrBundle {
+                mesh: assets.mesh.clone(),
+                material: assets.material.clone(),
+                transform: Transform::from_translation(birb.translation() + offset)
+                    .with_scale(Vec3::ZERO),
+                ..default()
+            },
+            WindStreak {
+                age: 0.0,
+                lifetime: rng.gen_range(STREAK_LIFETIME.0..STREAK_LIFETIME.1),
+            },
+        ));
+    }
+}
+
+fn move_wind_streaks(
+    mut commands: Commands,
+    time: Res<Time>,
+    wind: Res<WindField>,
+    mut streaks: Query<(Entity, &mut Transform, &mut WindStreak)>,
+    birb: Query<&GlobalTransform, With<Birb>>,
+) {
+    let birb_pos = birb.iter().next().map(|b| b.translation());
+    for (entity, mut transform, mut streak) in &mut streaks {
+        streak.age += time.delta_seconds();
+        let too_far = birb_pos
+            .map(|p| p.distance(transform.translation) > SPAWN_RADIUS.1 * 1.5)
+            .unwrap_or(true);
+        if streak.age > streak.lifetime || too_far {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let velocity = wind.sample(transform.translation) * STREAK_SPEED;
+        transform.translation += velocity * time.delta_seconds();
+        if let Some(dir) = velocity.try_normalize() {
+            transform.look_to(dir, Vec3::Y);
+        }
+        // Grow in, shrink out, and stretch with the wind speed.
+        let life = streak.age / streak.lifetime;
+        let fade = (life * 4.0).min((1.0 - life) * 4.0).min(1.0);
+        transform.scale = Vec3::new(fade, fade, fade * (0.3 + velocity.length() * 0.15));
+    }
+}
@@ -0,0 +1,343 @@
+use bevy::prelude::*;
+
+use crate::{
+    plugins::{
+        controls::{InputAction, InputBindings},
+        scan_codes::KeyLabels,
+        tuning::FlightParams,
+    },
+    BirbState,
+};
+
+/// Shows which key moves which joint: a home-row keyboard whose keys light up while pressed,
+/// laid out like `asset_sources/Typing-home-keys-hand-position.png`, next to the angle and
+/// angular velocity of every wing joint.
+pub(crate) struct WingHudPlugin;
+
+impl Plugin for WingHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_wing_hud).add_systems(
+            Update,
+            (
+                toggle_wing_hud,
+                update_key_labels,
+                update_keys,
+                update_joint_bars,
+            ),
+        );
+    }
+}
+
+#[derive(Component)]
+struct WingHud;
+
+/// A key of the drawn keyboard, lit up while its action is active.
+#[derive(Component)]
+struct HudKey(Option<InputAction>);
+
+#[derive(Component)]
+struct HudKeyLabel(InputAction);
+
+/// Fill showing the angle of the joint at this index in `BirbState::angles`.
+#[derive(Component)]
+struct AngleBar(usize);
+
+/// Fill showing the angular velocity of the joint at this index in `BirbState::angles`.
+#[derive(Component)]
+struct VelocityBar(usize);
+
+const KEY_SIZE: f32 = 34.0;
+const KEY_GAP: f32 = 4.0;
+const BAR_HEIGHT: f32 = 80.0;
+/// Angular velocity at which a velocity bar is full.
+const MAX_DISPLAYED_VELOCITY: f32 = 10.0;
+
+const PANEL_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.4);
+const UNUSED_KEY_COLOR: Color = Color::rgba(0.9, 0.9, 0.9, 0.2);
+const KEY_COLOR: Color = Color::rgba(0.55, 0.9, 0.55, 0.5);
+const PRESSED_COLOR: Color = Color::rgba(1.0, 0.843, 0.0, 0.9);
+const TRACK_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.15);
+const ANGLE_COLOR: Color = Color::rgb(0.55, 0.9, 0.55);
+const VELOCITY_NEGATIVE_COLOR: Color = Color::rgb(0.4, 0.7, 1.0);
+const VELOCITY_POSITIVE_COLOR: Color = Color::rgb(1.0, 0.5, 0.3);
+
+/// The keyboard rows, `None` for keys without an action. The rows are shifted like on a real
+/// keyboard.
+const KEY_ROWS: [(f32, &[Option<InputAction>]); 3] = [
+    (
+        0.0,
+        &[
+            Some(InputAction::LeftWingJoint(3)),
+            Some(InputAction::LeftWingJoint(2)),
+            Some(InputAction::LeftWingJoint(1)),
+            Some(InputAction::LeftWingJoint(0)),
+            None,
+            None,
+            Some(InputAction::RightWingJoint(0)),
+            Some(InputAction::RightWingJoint(1)),
+            Some(InputAction::RightWingJoint(2)),
+            Some(InputAction::RightWingJoint(3)),
+        ],
+    ),
+    (
+        0.5,
+        &[
+            None,
+            None,
+            None,
+            Some(InputAction::PitchUp),
+            None,
+            Some(InputAction::PitchDown),
+            None,
+        ],
+    ),
+    (2.5, &[Some(InputAction::Poop)]),
+];
+
+fn setup_wing_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+        font_size: 16.0,
+        color: Color::BLACK,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(10.0),
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            WingHud,
+        ))
+        .with_children(|root| {
+            root.spawn(NodeBundle {
+                style: Style {
+                    column_gap: Val::Px(16.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: PANEL_COLOR.into(),
+                ..default()
+            })
+            .with_children(|panel| {
+                // Keyboard
+                panel
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(KEY_GAP),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|keyboard| {
+                        for (offset, keys) in KEY_ROWS {
+                            keyboard
+                                .spawn(NodeBundle {
+                                    style: Style {
+                                        column_gap: Val::Px(KEY_GAP),
+                                        margin: UiRect::left(Val::Px(
+                                            offset * (KEY_SIZE + KEY_GAP),
+                                        )),
+                                        ..default()
+                                    },
+                                    ..default()
+                                })
+                                .with_children(|row| {
+                                    for &action in keys {
+                                        spawn_key(row, action, &text_style);
+                                    }
+                                });
+                        }
+                    });
+
+                // Joint angles and angular velocities, in keyboard order.
+                panel
+                    .spawn(NodeBundle {
+                        style: Style {
+                            column_gap: Val::Px(6.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|bars| {
+                        let joints = [3, 2, 1, 0]
+                            .map(InputAction::LeftWingJoint)
+                            .into_iter()
+                            .chain([0, 1, 2, 3].map(InputAction::RightWingJoint));
+                        for index in joints.filter_map(InputAction::joint_index) {
+                            spawn_joint_bars(bars, index);
+                        }
+                    });
+            });
+        });
+}
+
+fn spawn_key(row: &mut ChildBuilder, action: Option<InputAction>, text_style: &TextStyle) {
+    let width = match action {
+        Some(InputAction::Poop) => KEY_SIZE * 5.0 + KEY_GAP * 4.0,
+        _ => KEY_SIZE,
+    };
+    row.spawn((
+        NodeBundle {
+            style: Style {
+                width: Val::Px(width),
+                height: Val::Px(KEY_SIZE),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: match action {
+                Some(_) => KEY_COLOR.into(),
+                None => UNUSED_KEY_COLOR.into(),
+            },
+            ..default()
+        },
+        HudKey(action),
+    ))
+    .with_children(|key| {
+        if let Some(action) = action {
+            key.spawn((
+                TextBundle::from_section("", text_style.clone()),
+                HudKeyLabel(action),
+            ));
+        }
+    });
+}
+
+fn spawn_joint_bars(bars: &mut ChildBuilder, index: usize) {
+    let track = |width: f32| NodeBundle {
+        style: Style {
+            width: Val::Px(width),
+            height: Val::Px(BAR_HEIGHT),
+            ..default()
+        },
+        background_color: TRACK_COLOR.into(),
+        ..default()
+    };
+    let fill = |color: Color| NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            ..default()
+        },
+        background_color: color.into(),
+        ..default()
+    };
+    bars.spawn(NodeBundle {
+        style: Style {
+            column_gap: Val::Px(1.0),
+            ..default()
+        },
+        ..default()
+    })
+    .with_children(|joint| {
+        joint.spawn(track(10.0)).with_children(|track| {
+            track.spawn((fill(ANGLE_COLOR), AngleBar(index)));
+        });
+        joint.spawn(track(4.0)).with_children(|track| {
+            track.spawn((fill(VELOCITY_NEGATIVE_COLOR), VelocityBar(index)));
+        });
+    });
+}
+
+fn toggle_wing_hud(
+    actions: Res<Input<InputAction>>,
+    mut hud: Query<&mut Visibility, With<WingHud>>,
+) {
+    if actions.just_pressed(InputAction::ToggleHud) {
+        for mut visibility in &mut hud {
+            *visibility = match *visibility {
+                Visibility::Hidden => Visibility::Visible,
+                Visibility::Visible | Visibility::Inherited => Visibility::Hidden,
+            };
+        }
+    }
+}
+
+fn update_key_labels(
+    bindings: Res<InputBindings>,
+    key_labels: Res<KeyLabels>,
+    mut texts: Query<(&mut Text, &HudKeyLabel)>,
+) {
+    if !bindings.is_changed() && !key_labels.is_changed() {
+        return;
+    }
+    for (mut text, label) in &mut texts {
+        text.sections[0].value = bindings
+            .get(label.0)
+            .iter()
+            .find(|b| b.is_keyboard())
+            .map(|b| b.label(&key_labels))
+            .unwrap_or_default();
+    }
+}
+
+fn update_keys(
+    actions: Res<Input<InputAction>>,
+    analog_actions: Res<Axis<InputAction>>,
+    mut keys: Query<(&HudKey, &mut BackgroundColor)>,
+) {
+    for (key, mut color) in &mut keys {
+        let Some(action) = key.0 else {
+            continue;
+        };
+        let effort = if actions.pressed(action) {
+            1.0
+        } else {
+            analog_actions.get(action).unwrap_or(0.0)
+        };
+        let [r, g, b, a] = KEY_COLOR.as_rgba_f32();
+        let [pr, pg, pb, pa] = PRESSED_COLOR.as_rgba_f32();
+        let mix = |from: f32, to: f32| from + (to - from) * effort;
+        let wanted = Color::rgba(mix(r, pr), mix(g, pg), mix(b, pb), mix(a, pa));
+        if color.0 != wanted {
+            color.0 = wanted;
+        }
+    }
+}
+
+fn update_joint_bars(
+    birb_state: Res<BirbState>,
+    flight_params: Res<FlightParams>,
+    mut angle_bars: Query<(&AngleBar, &mut Style), Without<VelocityBar>>,
+    mut velocity_bars: Query<(&VelocityBar, &mut Style, &mut BackgroundColor), Without<AngleBar>>,
+) {
+    let range = flight_params.max_wing_angle - flight_params.min_wing_angle;
+    // Every change to a `Style` lays out the UI again, so only touch bars that moved.
+    for (bar, mut style) in &mut angle_bars {
+        let angle = birb_state.angles[bar.0];
+        let fraction = ((angle - flight_params.min_wing_angle) / range).clamp(0.0, 1.0);
+        let height = Val::Percent(fraction * 100.0);
+        if style.height != height || style.bottom != Val::Px(0.0) {
+            style.bottom = Val::Px(0.0);
+            style.height = height;
+        }
+    }
+    for (bar, mut style, mut color) in &mut velocity_bars {
+        let velocity = birb_state.angular_velocity[bar.0];
+        let fraction = (velocity.abs() / MAX_DISPLAYED_VELOCITY).min(1.0);
+        let height = Val::Percent(fraction * 50.0);
+        // Bars grow up while the joint moves towards its max angle.
+        let (top, bottom, wanted) = if velocity >= 0.0 {
+            (Val::Auto, Val::Percent(50.0), VELOCITY_POSITIVE_COLOR)
+        } else {
+            (Val::Percent(50.0), Val::Auto, VELOCITY_NEGATIVE_COLOR)
+        };
+        if style.height != height || style.top != top || style.bottom != bottom {
+            style.height = height;
+            style.top = top;
+            style.bottom = bottom;
+        }
+        if color.0 != wanted {
+            color.0 = wanted;
+        }
+    }
+}